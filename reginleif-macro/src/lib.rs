@@ -71,20 +71,20 @@ pub fn base_store_point(item:TokenStream) -> TokenStream{
 fn impl_storage(ast:DeriveInput) -> TokenStream{
     let ident = ast.ident;
 
-    let filepath = ast.attrs.iter().filter(
+    let filepath = ast.attrs.iter().find(
         |x| x.path().is_ident("filepath")
-    ).nth(0).expect("required #[filepath(&'static [&static str])] to use this derive!");
+    ).expect("required #[filepath(&'static [&static str])] to use this derive!");
 
     let filepath = match &filepath.meta {
         Meta::List(a) => a.tokens.clone(),
         _o=> panic!("error while parsing argument!")
     };
 
-    let base_on = ast.attrs.iter().filter(
+    let base_on = ast.attrs.iter().find(
         |x| x.path().is_ident("base_on")
-    ).nth(0);
+    );
 
-    return if let Some(base_on) = base_on {
+    if let Some(base_on) = base_on {
         let base_on = match &base_on.meta {
             Meta::List(a) => a.tokens.clone(),
             _o=> panic!("error while parsing argument!")
@@ -115,15 +115,14 @@ fn impl_storage(ast:DeriveInput) -> TokenStream{
 #[proc_macro_derive(Storage, attributes(base_on,filepath))]
 pub fn storage(item: TokenStream) -> TokenStream {
     let ast:DeriveInput = syn::parse(item).unwrap();
-    let implement = impl_storage(ast);
-    implement
+    impl_storage(ast)
 }
 
 fn impl_save(ast: DeriveInput) -> TokenStream{
     let ident = ast.ident;
-    let base_on = ast.attrs.iter().filter(
+    let base_on = ast.attrs.iter().find(
         |x| x.path().is_ident("base_on")
-    ).nth(0);
+    );
 
     if let Some(base_on) = base_on {
         let base_on = match &base_on.meta {
@@ -154,15 +153,14 @@ fn impl_save(ast: DeriveInput) -> TokenStream{
 #[proc_macro_derive(Save, attributes(base_on))]
 pub fn save(item: TokenStream) -> TokenStream {
     let ast:DeriveInput = syn::parse(item).unwrap();
-    let implement = impl_save(ast);
-    implement
+    impl_save(ast)
 }
 
 fn impl_load(ast: DeriveInput) -> TokenStream{
     let ident = ast.ident;
-    let base_on = ast.attrs.iter().filter(
+    let base_on = ast.attrs.iter().find(
         |x| x.path().is_ident("base_on")
-    ).nth(0);
+    );
 
      if let Some(base_on) = base_on {
         let base_on = match &base_on.meta {
@@ -195,15 +193,14 @@ fn impl_load(ast: DeriveInput) -> TokenStream{
 #[proc_macro_derive(Load, attributes(base_on))]
 pub fn load(item: TokenStream) -> TokenStream {
     let ast:DeriveInput = syn::parse(item).unwrap();
-    let implement = impl_load(ast);
-    implement
+    impl_load(ast)
 }
 
 fn impl_cache(ast: DeriveInput) -> TokenStream{
     let ident = ast.ident;
-    let base_on = ast.attrs.iter().filter(
+    let base_on = ast.attrs.iter().find(
         |x| x.path().is_ident("base_on")
-    ).nth(0);
+    );
 
     if let Some(base_on) = base_on {
         let base_on = match &base_on.meta {
@@ -235,6 +232,5 @@ fn impl_cache(ast: DeriveInput) -> TokenStream{
 #[proc_macro_derive(Cache, attributes(base_on))]
 pub fn cache(item: TokenStream) -> TokenStream {
     let ast:DeriveInput = syn::parse(item).unwrap();
-    let implement = impl_cache(ast);
-    implement
+    impl_cache(ast)
 }
\ No newline at end of file
@@ -1,3 +1,47 @@
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod device_login;
+#[cfg(test)]
+mod auth_code;
+#[cfg(test)]
+mod microsoft;
+#[cfg(test)]
+mod error;
+#[cfg(test)]
+mod xbox;
+#[cfg(test)]
+mod entitlement;
+#[cfg(test)]
+mod skin;
+#[cfg(test)]
+mod name;
+#[cfg(test)]
+mod offline;
+#[cfg(test)]
+mod yggdrasil;
+#[cfg(test)]
+mod manager;
+#[cfg(test)]
+mod scheduler;
+#[cfg(test)]
+mod claims;
+#[cfg(test)]
+mod certificate;
+#[cfg(test)]
+mod attribute;
+#[cfg(test)]
+mod texture;
+#[cfg(test)]
+mod lookup;
+#[cfg(test)]
+mod rate_limit;
+#[cfg(test)]
+mod import;
+#[cfg(test)]
+mod http;
+#[cfg(test)]
+mod validate;
 
 #[cfg(test)]
 mod test{
@@ -5,16 +49,19 @@ mod test{
     use reginleif::auth::account::Account;
     use reginleif::auth::microsoft::{DeviceCode, MicrosoftAuthError};
     use reginleif::auth::minecraft::{MinecraftAuth, Profile};
+    use reginleif::auth::minecraft::entitlement::GameOwnership;
     use reginleif::auth::xbox::{XboxLiveToken, XboxSecurityToken};
+    use reginleif::http::HttpContext;
     use reginleif_utils::expiring_data::ExpiringData;
+    use super::mock;
 
     #[tokio::test]
     #[ignore]
     async fn test_auth_token(){
 
-        let client = reqwest::Client::new();
+        let ctx = HttpContext::default();
         let client_id = "47f3e635-2886-4628-a1c2-fd8a9f4d7a5f";
-        let res = DeviceCode::fetch(&client,client_id).await;
+        let res = DeviceCode::fetch(&ctx,client_id).await;
 
         let device_code = match res{
             Ok(device_code) => {
@@ -29,7 +76,7 @@ mod test{
 
 
         let mut res = loop{
-            let result = device_code.exchange(&client,client_id).await;
+            let result = device_code.exchange(&ctx,client_id).await;
             let res = match result{
                 Ok(res) => {res}
                 Err(e) => {
@@ -49,27 +96,60 @@ mod test{
 
         let cloned = res.clone();
 
-        let xbox_live_token = XboxLiveToken::fetch(&client,&res.data.access_token).await.unwrap();
+        let xbox_live_token = XboxLiveToken::fetch(&ctx,res.data.access_token.expose()).await.unwrap();
         println!("{:?}",xbox_live_token);
-        let xbox_security_token = XboxSecurityToken::fetch(&client,xbox_live_token).await.unwrap();
+        let xbox_security_token = XboxSecurityToken::fetch(&ctx,xbox_live_token).await.unwrap();
         println!("{:?}",xbox_security_token);
 
-        let minecraft_auth = MinecraftAuth::fetch(&client,xbox_security_token).await.unwrap();
+        let minecraft_auth = MinecraftAuth::fetch(&ctx,xbox_security_token).await.unwrap();
         println!("{:?}",minecraft_auth);
-        let profile = Profile::fetch(&client,&minecraft_auth).await.unwrap();
+        let profile = Profile::fetch(&ctx,&minecraft_auth).await.unwrap();
         println!("{:?}",profile);
 
         let account:Account = (minecraft_auth,profile,res.clone()).into();
         let mut account: ExpiringData<Account> = account.into();
-        account.refresh(&client_id.to_string()).await.unwrap();
+        account.refresh(&(ctx.clone(),client_id.to_string())).await.unwrap();
         println!("{:?}",account);
 
         tokio::time::sleep(Duration::from_secs(2)).await;
-        res.refresh(&(client,client_id.to_string())).await.unwrap();
+        res.refresh(&(ctx,client_id.to_string())).await.unwrap();
 
         assert_ne!(cloned.created_at,res.created_at);
 
 
     }
 
+    #[tokio::test]
+    async fn test_auth_chain_with_mock_endpoints(){
+
+        let server = wiremock::MockServer::start().await;
+        mock::mount_microsoft(&server).await;
+        mock::mount_xbox_minecraft(&server).await;
+
+        let ctx = mock::context(&server);
+
+        let device_code = DeviceCode::fetch(&ctx,mock::CLIENT_ID).await.unwrap();
+        assert_eq!(device_code.user_code,"MOCKCODE");
+
+        let msa = device_code.exchange(&ctx,mock::CLIENT_ID).await.unwrap();
+        assert_eq!(msa.data.access_token,"msa-access");
+
+        let xbox_live_token = XboxLiveToken::fetch(&ctx,msa.data.access_token.expose()).await.unwrap();
+        let xbox_security_token = XboxSecurityToken::fetch(&ctx,xbox_live_token).await.unwrap();
+        assert_eq!(xbox_security_token.uhs,"mock-uhs");
+
+        let minecraft_auth = MinecraftAuth::fetch(&ctx,xbox_security_token).await.unwrap();
+        assert_eq!(minecraft_auth.access_token,"mc-access");
+
+        let profile = Profile::fetch(&ctx,&minecraft_auth).await.unwrap();
+        assert_eq!(profile.name,"Notch");
+
+        let account:Account = (minecraft_auth,profile,msa).into();
+        let mut account: ExpiringData<Account> = account.into();
+        account.refresh(&(ctx.clone(),mock::CLIENT_ID.to_string())).await.unwrap();
+        assert_eq!(account.data.profile.id,"069a79f444e94726a5befca90e38aaf5");
+        assert_eq!(account.data.ownership,GameOwnership::Purchased);
+
+    }
+
 }
\ No newline at end of file
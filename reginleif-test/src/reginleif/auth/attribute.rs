@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod test{
+    use chrono::Local;
+    use reginleif::auth::minecraft::attribute::PlayerAttributes;
+    use serde_json::json;
+    use wiremock::matchers::{body_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use super::super::mock;
+
+    fn attributes_json(filter:bool,ban_expires:Option<i64>) -> serde_json::Value{
+        let mut value = json!({
+            "privileges": {
+                "onlineChat": {"enabled": true},
+                "multiplayerServer": {"enabled": true},
+                "multiplayerRealms": {"enabled": false},
+                "telemetry": {"enabled": true},
+                "optionalTelemetry": {"enabled": false}
+            },
+            "profanityFilterPreferences": {"profanityFilterOn": filter},
+            "banStatus": {"bannedScopes": {}}
+        });
+        if let Some(expires) = ban_expires {
+            value["banStatus"]["bannedScopes"]["MULTIPLAYER"] = json!({
+                "banId": "ban-id",
+                "expires": expires,
+                "reason": "false_reporting",
+                "reasonMessage": "Banned for false reporting"
+            });
+        }
+        value
+    }
+
+    #[tokio::test]
+    async fn test_attributes(){
+        let hour = 3600 * 1000;
+        let now = Local::now().timestamp_millis();
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/player/attributes"))
+            .and(header("Authorization","Bearer mc-access"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(attributes_json(false,Some(now + hour))))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/player/attributes"))
+            .and(body_json(json!({"profanityFilterPreferences": {"profanityFilterOn": true}})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(attributes_json(true,Some(now - hour))))
+            .mount(&server)
+            .await;
+
+        let ctx = mock::context(&server);
+
+        let attributes = PlayerAttributes::fetch(&ctx,&mock::minecraft_auth()).await.unwrap();
+        assert!(attributes.privileges.online_chat.enabled);
+        assert!(!attributes.privileges.multiplayer_realms.enabled);
+        assert!(!attributes.profanity_filter_preferences.profanity_filter_on);
+        let ban = attributes.ban_status.multiplayer().unwrap();
+        assert_eq!(ban.ban_id,"ban-id");
+        assert_eq!(ban.expires_at().unwrap().timestamp_millis(),now + hour);
+        assert!(!attributes.can_join_servers());
+
+        let attributes = PlayerAttributes::set_profanity_filter(&ctx,&mock::minecraft_auth(),true).await.unwrap();
+        assert!(attributes.profanity_filter_preferences.profanity_filter_on);
+        assert!(attributes.ban_status.multiplayer().is_none()); // the ban is over.
+        assert!(attributes.can_join_servers());
+        assert!(!attributes.can_join_realms());
+
+        let attributes:PlayerAttributes = serde_json::from_value(json!({})).unwrap();
+        assert!(!attributes.can_join_servers());
+    }
+
+}
@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod test{
+    use std::collections::HashMap;
+    use std::time::Duration;
+    use reginleif::auth::microsoft::auth_code::{AuthCodeLogin, Pkce};
+    use reginleif::auth::microsoft::MicrosoftAuthError;
+    use tokio::net::TcpStream;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use super::super::mock;
+
+    #[test]
+    fn test_pkce_challenge(){
+        // see RFC 7636 Appendix B
+        let pkce = Pkce::from_verifier("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk");
+        assert_eq!(pkce.challenge,"E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM");
+
+        let pkce = Pkce::generate();
+        assert_eq!(pkce.verifier.len(),43);
+        assert_ne!(pkce,Pkce::generate());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_authorize_endpoint(){
+        let server = MockServer::start().await;
+        let mut ctx = mock::context(&server);
+        ctx.endpoints.authorize = "not a url".to_string();
+
+        let err = AuthCodeLogin::bind(ctx,mock::CLIENT_ID).await.unwrap_err();
+        assert!(matches!(err,MicrosoftAuthError::Others(_)));
+    }
+
+    #[tokio::test]
+    async fn test_auth_code_login(){
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/consumers/oauth2/v2.0/token"))
+            .and(body_string_contains("grant_type=authorization_code"))
+            .and(body_string_contains("code=mock-code"))
+            .and(body_string_contains("code_verifier="))
+            .respond_with(ResponseTemplate::new(200).set_body_json(mock::microsoft_auth_json("msa-access","msa-refresh")))
+            .mount(&server)
+            .await;
+
+        let login = AuthCodeLogin::bind(mock::context(&server),mock::CLIENT_ID).await.unwrap();
+        let url = login.authorize_url();
+        let query:HashMap<_,_> = url.query_pairs().into_owned().collect();
+        assert_eq!(query["redirect_uri"],login.redirect_uri());
+        assert!(login.redirect_uri().starts_with("http://127.0.0.1:"));
+        assert_eq!(query["code_challenge_method"],"S256");
+
+        let redirect = format!("{}/?code=mock-code&state={}",login.redirect_uri(),query["state"]);
+        let handle = tokio::spawn(async move { login.wait().await });
+
+        let client = reqwest::Client::new();
+        let favicon = client.get(format!("{}/favicon.ico",query["redirect_uri"])).send().await.unwrap();
+        assert_eq!(favicon.status(),404);
+        let page = client.get(redirect).send().await.unwrap();
+        assert!(page.status().is_success());
+
+        let msa = handle.await.unwrap().unwrap();
+        assert_eq!(msa.data.refresh_token,"msa-refresh");
+    }
+
+    #[tokio::test]
+    async fn test_auth_code_ignores_stray_requests(){
+        let server = MockServer::start().await;
+        let login = AuthCodeLogin::bind(mock::context(&server),mock::CLIENT_ID).await.unwrap();
+        let state = login.authorize_url().query_pairs().find(|(k,_)| k == "state").unwrap().1.to_string();
+        let redirect_uri = login.redirect_uri().to_string();
+        let handle = tokio::spawn(async move { login.wait().await });
+
+        // connect and close without sending anything, like a port probe.
+        drop(TcpStream::connect(redirect_uri.trim_start_matches("http://")).await.unwrap());
+
+        let page = reqwest::get(format!("{redirect_uri}/?code=mock-code&state=forged")).await.unwrap();
+        assert_eq!(page.status(),400);
+        let page = reqwest::get(format!("{redirect_uri}/?state={state}")).await.unwrap();
+        assert_eq!(page.status(),400);
+        assert!(!handle.is_finished());
+
+        let page = reqwest::get(format!("{redirect_uri}/?error=access_denied&state={state}")).await.unwrap();
+        assert_eq!(page.status(),400);
+        assert!(matches!(handle.await.unwrap(),Err(MicrosoftAuthError::AuthorizationDeclined)));
+    }
+
+    #[tokio::test]
+    async fn test_auth_code_idle_connection_does_not_block(){
+        let server = MockServer::start().await;
+        let login = AuthCodeLogin::bind(mock::context(&server),mock::CLIENT_ID).await.unwrap();
+        let state = login.authorize_url().query_pairs().find(|(k,_)| k == "state").unwrap().1.to_string();
+        let redirect_uri = login.redirect_uri().to_string();
+        let handle = tokio::spawn(async move { login.wait().await });
+
+        // keep a speculative connection of the browser open without sending anything.
+        let _idle = TcpStream::connect(redirect_uri.trim_start_matches("http://")).await.unwrap();
+
+        let page = reqwest::get(format!("{redirect_uri}/?error=access_denied&state={state}")).await.unwrap();
+        assert_eq!(page.status(),400);
+        let result = tokio::time::timeout(Duration::from_secs(1),handle).await.unwrap().unwrap();
+        assert!(matches!(result,Err(MicrosoftAuthError::AuthorizationDeclined)));
+    }
+
+}
@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod test{
+    use std::time::Duration;
+    use base64::Engine;
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use chrono::Local;
+    use reginleif::auth::error::AuthError;
+    use reginleif::auth::minecraft::claims::MinecraftTokenClaims;
+    use reginleif::auth::minecraft::MinecraftAuth;
+    use reginleif_utils::expiring_data::ExpiringData;
+    use serde_json::json;
+    use super::super::mock;
+
+    fn jwt(exp:i64) -> String{
+        let header = URL_SAFE_NO_PAD.encode(json!({"kid": "1", "alg": "RS256"}).to_string());
+        let payload = URL_SAFE_NO_PAD.encode(json!({
+            "xuid": "2535400000000000",
+            "agg": "Adult",
+            "sub": "mock-username",
+            "auth": "XBOX",
+            "ns": "default",
+            "roles": [],
+            "iss": "authentication",
+            "profiles": {"mc": "069a79f444e94726a5befca90e38aaf5"},
+            "platform": "PC_LAUNCHER",
+            "nbf": exp - 86400,
+            "exp": exp,
+            "iat": exp - 86400
+        }).to_string());
+        format!("{header}.{payload}.signature")
+    }
+
+    fn mc_auth(token:String) -> MinecraftAuth{
+        let mut mc_auth = mock::minecraft_auth();
+        mc_auth.access_token = token.into();
+        mc_auth
+    }
+
+    #[test]
+    fn test_decode_claims(){
+        let exp = Local::now().timestamp() + 3600;
+        let claims = mc_auth(jwt(exp)).claims().unwrap();
+        assert_eq!(claims.xuid.as_deref(),Some("2535400000000000"));
+        assert_eq!(claims.sub,"mock-username");
+        assert_eq!(claims.profile_id(),Some("069a79f444e94726a5befca90e38aaf5"));
+        assert_eq!(claims.platform.as_deref(),Some("PC_LAUNCHER"));
+        assert_eq!(claims.expires_at().unwrap().timestamp(),exp);
+        assert_eq!(claims.issued_at().unwrap().timestamp(),exp - 86400);
+
+        let err = MinecraftTokenClaims::decode("not-a-jwt").unwrap_err();
+        assert!(matches!(err,AuthError::InvalidResponse(_)));
+    }
+
+    #[test]
+    fn test_expiry_from_token(){
+        // loaded from disk long after it's created, but the token is still valid for an hour.
+        let mut data:ExpiringData<_> = mc_auth(jwt(Local::now().timestamp() + 3600)).into();
+        data.created_at = Local::now() - chrono::Duration::days(2);
+        assert!(!data.is_expired());
+        assert!(data.expire_in() <= Duration::from_secs(3600));
+        assert!(data.expire_in() > Duration::from_secs(3500));
+
+        // the token is expired even though expires_in says it's not.
+        let data:ExpiringData<_> = mc_auth(jwt(Local::now().timestamp() - 10)).into();
+        assert!(data.is_expired());
+        assert_eq!(data.expire_in(),Duration::ZERO);
+
+        // fall back to expires_in if the token is not a JWT.
+        let data:ExpiringData<_> = mc_auth("opaque".to_string()).into();
+        assert!(!data.is_expired());
+        assert!(data.expire_in() > Duration::from_secs(86000));
+    }
+
+}
@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod test{
+    use std::time::Duration;
+    use tokio_stream::StreamExt;
+    use reginleif::auth::microsoft::device_login::{DeviceCodeLogin, DeviceCodeState};
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use super::super::mock;
+
+    async fn collect(server:&MockServer) -> Vec<DeviceCodeState>{
+        let login = DeviceCodeLogin::new(mock::context(server),mock::CLIENT_ID);
+        login.start().collect().await
+    }
+
+    #[tokio::test]
+    async fn test_pending_then_success(){
+        let server = MockServer::start().await;
+        mock::mount_device_code(&server).await;
+        mock::mount_token_error(&server,"authorization_pending",2).await;
+        mock::mount_token(&server).await;
+
+        let states = collect(&server).await;
+
+        assert!(matches!(states.first(),Some(DeviceCodeState::CodeIssued(code)) if code.user_code == "MOCKCODE"));
+        let waiting = states.iter().filter(|x| matches!(x,DeviceCodeState::Waiting{..})).count();
+        assert_eq!(waiting,3);
+        match states.last() {
+            Some(DeviceCodeState::Success(msa)) => assert_eq!(msa.data.access_token,"msa-access"),
+            other => panic!("unexpected state: {:?}",other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_declined_and_expired(){
+        let server = MockServer::start().await;
+        mock::mount_device_code(&server).await;
+        mock::mount_token_error(&server,"authorization_declined",1).await;
+        mock::mount_token_error(&server,"expired_token",1).await;
+
+        let states = collect(&server).await;
+        assert!(matches!(states.last(),Some(DeviceCodeState::Declined)));
+
+        let states = collect(&server).await;
+        assert!(matches!(states.last(),Some(DeviceCodeState::Expired)));
+    }
+
+    #[tokio::test]
+    async fn test_cancel(){
+        let server = MockServer::start().await;
+        mock::mount_device_code(&server).await;
+        mock::mount_token_error(&server,"authorization_pending",u64::MAX).await;
+
+        let login = DeviceCodeLogin::new(mock::context(&server),mock::CLIENT_ID);
+        let mut stream = login.start();
+        let cancel = stream.cancel_handle();
+
+        let mut last = None;
+        while let Some(state) = stream.next().await {
+            if let DeviceCodeState::CodeIssued(_) = state {
+                cancel.cancel();
+            }
+            last = Some(state);
+        }
+
+        assert!(cancel.is_cancelled());
+        assert!(matches!(last,Some(DeviceCodeState::Cancelled)));
+    }
+
+    #[tokio::test]
+    async fn test_drop_stops_polling(){
+        let server = MockServer::start().await;
+        let mut device_code = mock::device_code_json();
+        device_code["interval"] = 1.into();
+        Mock::given(method("POST"))
+            .and(path("/consumers/oauth2/v2.0/devicecode"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(device_code))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/consumers/oauth2/v2.0/token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(mock::microsoft_auth_json("msa-access","msa-refresh")))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let login = DeviceCodeLogin::new(mock::context(&server),mock::CLIENT_ID);
+        let mut stream = login.start();
+        let cancel = stream.cancel_handle();
+        assert!(matches!(stream.next().await,Some(DeviceCodeState::CodeIssued(_))));
+        drop(stream);
+
+        assert!(cancel.is_cancelled());
+        tokio::time::sleep(Duration::from_millis(1500)).await; // longer than the polling interval.
+    }
+
+}
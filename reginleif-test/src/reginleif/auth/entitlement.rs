@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod test{
+    use reginleif::auth::account::{Account, AccountSetup};
+    use reginleif::auth::minecraft::entitlement::{Entitlements, GameOwnership};
+    use wiremock::matchers::{header, method, path, query_param_is_missing};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use super::super::mock;
+
+    async fn license(items:&[(&str,&str)]) -> Entitlements{
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/entitlements/license"))
+            .and(header("Authorization","Bearer mc-access"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(mock::entitlements_json(items)))
+            .mount(&server)
+            .await;
+
+        Entitlements::fetch_license(&mock::context(&server),&mock::minecraft_auth()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_ownership(){
+        let res = license(&[("product_minecraft","PURCHASE"),("game_minecraft","PURCHASE")]).await;
+        assert!(res.owns_java());
+        assert_eq!(res.ownership(),GameOwnership::Purchased);
+        assert_eq!(res.key_id,"1");
+
+        let res = license(&[("product_minecraft","GAMEPASS"),("game_minecraft","GAMEPASS")]).await;
+        assert_eq!(res.ownership(),GameOwnership::GamePass);
+        assert!(res.ownership().owns_game());
+
+        let res = license(&[("product_minecraft_bedrock","PURCHASE")]).await;
+        assert!(!res.owns_java());
+        assert_eq!(res.ownership(),GameOwnership::NotOwned);
+        assert!(!res.ownership().owns_game());
+
+        let res = license(&[]).await;
+        assert_eq!(res.ownership(),GameOwnership::NotOwned);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_store(){
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/entitlements/mcstore"))
+            .and(query_param_is_missing("requestId"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"name": "product_minecraft", "signature": "jwt"}],
+                "signature": "mock-jwt",
+                "keyId": "1"
+            })))
+            .mount(&server)
+            .await;
+
+        let res = Entitlements::fetch_store(&mock::context(&server),&mock::minecraft_auth()).await.unwrap();
+        assert!(res.owns_java());
+        assert_eq!(res.request_id,None);
+        assert_eq!(res.items[0].source,None);
+    }
+
+    #[tokio::test]
+    async fn test_sign_in_without_entitlements(){
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/entitlements/license"))
+            .respond_with(ResponseTemplate::new(503))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        mock::mount_xbox_minecraft(&server).await;
+
+        let AccountSetup::Ready(account) = Account::from_msa(&mock::context(&server),mock::microsoft_auth().into()).await.unwrap() else {
+            panic!("the profile should exist");
+        };
+        assert_eq!(account.ownership,GameOwnership::Unknown);
+        assert_eq!(account.mc_auth.access_token,"mc-access");
+    }
+
+}
@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod test{
+    use std::time::Duration;
+    use reginleif::auth::error::{AuthError, MinecraftServicesError};
+    use reginleif::auth::minecraft::Profile;
+    use reginleif::auth::xbox::{XboxLiveToken, XboxSecurityError, XboxSecurityToken};
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use super::super::mock;
+
+    async fn mount_profile(server:&MockServer,response:ResponseTemplate){
+        Mock::given(method("GET"))
+            .and(path("/minecraft/profile"))
+            .respond_with(response)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_minecraft_errors(){
+        let server = MockServer::start().await;
+        mount_profile(&server,ResponseTemplate::new(404)).await;
+        let err = Profile::fetch(&mock::context(&server),&mock::minecraft_auth()).await.unwrap_err();
+        assert!(matches!(err,AuthError::ProfileNotFound));
+        assert!(!err.is_retryable());
+
+        let server = MockServer::start().await;
+        mount_profile(&server,ResponseTemplate::new(401)).await;
+        let err = Profile::fetch(&mock::context(&server),&mock::minecraft_auth()).await.unwrap_err();
+        assert!(matches!(err,AuthError::Minecraft(MinecraftServicesError::Unauthorized)));
+
+        let server = MockServer::start().await;
+        mount_profile(&server,ResponseTemplate::new(429).insert_header("Retry-After","30")).await;
+        let err = Profile::fetch(&mock::context(&server),&mock::minecraft_auth()).await.unwrap_err();
+        assert!(matches!(
+            err,
+            AuthError::Minecraft(MinecraftServicesError::TooManyRequests{retry_after:Some(x)}) if x == Duration::from_secs(30)
+        ));
+        assert!(err.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_http_error(){
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/user/authenticate"))
+            .respond_with(ResponseTemplate::new(503).set_body_string("maintenance"))
+            .mount(&server)
+            .await;
+
+        let err = XboxLiveToken::fetch(&mock::context(&server),"msa-access").await.unwrap_err();
+        match &err {
+            AuthError::Http{status,body,..} => {
+                assert_eq!(status.as_u16(),503);
+                assert_eq!(body,"maintenance");
+            }
+            other => panic!("unexpected error: {:?}",other),
+        }
+        assert!(err.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_xsts_status_error(){
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/xsts/authorize"))
+            .respond_with(ResponseTemplate::new(400).set_body_string("<html>Bad Request</html>"))
+            .mount(&server)
+            .await;
+
+        let err = XboxSecurityToken::fetch(&mock::context(&server),XboxLiveToken::from("xbl-token")).await.unwrap_err();
+        assert!(matches!(&err,XboxSecurityError::Status{status,body} if status.as_u16() == 400 && body.contains("Bad Request")));
+        assert!(!AuthError::from(err).is_retryable());
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/xsts/authorize"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("not json"))
+            .mount(&server)
+            .await;
+
+        let err = XboxSecurityToken::fetch(&mock::context(&server),XboxLiveToken::from("xbl-token")).await.unwrap_err();
+        assert!(matches!(err,XboxSecurityError::ReqwestError(_)));
+        assert!(!AuthError::from(err).is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_account_needs_relogin(){
+        let server = MockServer::start().await;
+        mock::mount_token_error(&server,"invalid_grant",1).await;
+
+        let mut account = mock::account();
+        account.msa.data.expires_in = Duration::ZERO;
+        account.msa.data.refresh_token = "revoked".into();
+
+        let err = account.renew(&mock::context(&server),mock::CLIENT_ID).await.unwrap_err();
+        assert!(err.needs_relogin());
+        assert!(!err.is_retryable());
+    }
+
+}
@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod test{
+    use std::time::Duration;
+    use reginleif::auth::account::Account;
+    use reginleif::auth::minecraft::Profile;
+    use reginleif::http::{HttpContext, DEFAULT_USER_AGENT};
+    use reginleif_utils::expiring_data::ExpiringData;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+    use super::super::mock;
+
+    fn account() -> ExpiringData<Account>{
+        let mut account = mock::account();
+        account.msa.data.expires_in = Duration::ZERO;
+        account.msa.data.access_token = "msa-old".into();
+        account.mc_auth.expires_in = Duration::ZERO;
+        account.mc_auth.access_token = "mc-old".into();
+        account.into()
+    }
+
+    #[tokio::test]
+    async fn test_default_user_agent(){
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/minecraft/profile"))
+            .and(header("user-agent",DEFAULT_USER_AGENT))
+            .respond_with(ResponseTemplate::new(200).set_body_json(mock::profile_json()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let profile = Profile::fetch(&mock::context(&server),&mock::minecraft_auth()).await.unwrap();
+        assert_eq!(profile.name,"Notch");
+    }
+
+    #[tokio::test]
+    async fn test_refresh_with_context(){
+        let server = MockServer::start().await;
+        // any request without the user agent of the context fails the refresh.
+        Mock::given(|req:&Request| req.headers.get("user-agent").is_none_or(|x| x != "MyLauncher/1.0"))
+            .respond_with(ResponseTemplate::new(500))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        mock::mount_microsoft(&server).await;
+        mock::mount_xbox_minecraft(&server).await;
+
+        let ctx = HttpContext::builder()
+            .user_agent("MyLauncher/1.0")
+            .timeout(Duration::from_secs(10))
+            .endpoints(mock::endpoints(&server))
+            .build()
+            .unwrap();
+
+        let mut account = account();
+        account.refresh(&(ctx.clone(),mock::CLIENT_ID.to_string())).await.unwrap();
+        assert_eq!(account.data.mc_auth.access_token,"mc-access");
+        assert_eq!(account.data.msa.data.access_token,"msa-access");
+
+        let default = HttpContext::default().with_endpoints(ctx.endpoints.clone());
+        let err = Profile::fetch(&default,&account.data.mc_auth).await;
+        assert!(err.is_err());
+    }
+
+}
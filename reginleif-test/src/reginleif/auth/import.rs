@@ -0,0 +1,190 @@
+#[cfg(test)]
+mod test{
+    use chrono::{Duration, Local};
+    use reginleif::auth::account::AccountKind;
+    use reginleif::auth::import::{import_official, import_prism, ImportError};
+    use reginleif::auth::minecraft::entitlement::GameOwnership;
+    use serde_json::json;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use super::super::mock;
+
+    fn prism_token(token:Option<&str>,refresh:Option<&str>,expired:bool) -> serde_json::Value{
+        let now = Local::now();
+        let (iat,exp) = if expired {
+            (now - Duration::days(2),now - Duration::days(1))
+        } else {
+            (now - Duration::hours(1),now + Duration::hours(23))
+        };
+        json!({
+            "token": token,
+            "refresh_token": refresh,
+            "iat": iat.timestamp(),
+            "exp": exp.timestamp(),
+            "extra": {}
+        })
+    }
+
+    fn prism_profile(id:&str,name:&str) -> serde_json::Value{
+        json!({
+            "id": id,
+            "name": name,
+            "skin": {"id": "skin-id", "url": "http://textures.minecraft.net/texture/prism", "variant": "SLIM"},
+            "capes": [{"id": "cape-id", "url": "http://textures.minecraft.net/texture/cape", "alias": "Migrator"}],
+            "cape": "cape-id"
+        })
+    }
+
+    #[tokio::test]
+    async fn test_import_prism(){
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/consumers/oauth2/v2.0/token"))
+            .and(body_string_contains("client_id=prism-client"))
+            .and(body_string_contains("refresh_token=old-refresh"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(mock::microsoft_auth_json("msa-access","msa-refresh")))
+            .expect(1)
+            .mount(&server)
+            .await;
+        mock::mount_xbox_minecraft(&server).await;
+
+        let file = json!({
+            "formatVersion": 3,
+            "accounts": [
+                {
+                    "type": "MSA",
+                    "active": true,
+                    "msa": prism_token(Some("prism-msa"),Some("prism-refresh"),false),
+                    "ygg": prism_token(Some("prism-mc"),None,false),
+                    "profile": prism_profile("853c80ef3c3749fdaa49938b674adae6","jeb_"),
+                    "entitlement": {"canPlayMinecraft": true, "ownsMinecraft": false}
+                },
+                {
+                    "type": "MSA",
+                    "msa-client-id": "prism-client",
+                    "msa": prism_token(Some("old-msa"),Some("old-refresh"),true),
+                    "ygg": prism_token(Some("old-mc"),None,true),
+                    "profile": prism_profile("069a79f444e94726a5befca90e38aaf5","Notch")
+                },
+                {
+                    "type": "MSA",
+                    "msa": prism_token(Some("old-msa"),None,true),
+                    "ygg": prism_token(Some("old-mc"),None,true),
+                    "profile": prism_profile("61699b2ed3274a019f1e0ea8c3f06bc6","Dinnerbone")
+                },
+                {
+                    "type": "Offline",
+                    "ygg": {"token": "0", "iat": 0},
+                    "profile": {"id": "b50ad385829d3141a2167e7d7539ba7f", "name": "Steve", "capes": []}
+                },
+                {
+                    "type": "Mojang",
+                    "profile": {"id": "legacy", "name": "Legacy"}
+                },
+                {
+                    "profile": {"name": "Broken"}
+                }
+            ]
+        });
+
+        let path = std::env::temp_dir().join(format!("prism_accounts_{}.json",uuid::Uuid::new_v4()));
+        tokio::fs::write(&path,file.to_string()).await.unwrap();
+        let report = import_prism(&path,&mock::context(&server),mock::CLIENT_ID).await.unwrap();
+        tokio::fs::remove_file(&path).await.unwrap();
+
+        assert_eq!(report.accounts.len(),3);
+
+        let AccountKind::Microsoft(jeb) = &report.accounts[0] else { panic!("not a Microsoft account") };
+        assert!(!jeb.is_expired());
+        assert_eq!(jeb.data.mc_auth.access_token,"prism-mc");
+        assert_eq!(jeb.data.msa.data.refresh_token,"prism-refresh");
+        assert_eq!(jeb.data.ownership,GameOwnership::GamePass);
+        assert_eq!(jeb.data.profile.active_skin().unwrap().texture_key,"prism");
+        assert_eq!(jeb.data.profile.active_cape().unwrap().alias,"Migrator");
+
+        let AccountKind::Microsoft(notch) = &report.accounts[1] else { panic!("not a Microsoft account") };
+        assert_eq!(notch.data.mc_auth.access_token,"mc-access"); // refreshed
+        assert_eq!(notch.data.msa.data.refresh_token,"msa-refresh");
+        assert_eq!(notch.data.ownership,GameOwnership::Purchased);
+
+        assert!(matches!(&report.accounts[2],AccountKind::Offline(x) if x.name == "Steve"));
+
+        let failed:Vec<_> = report.failed.iter().map(|x| x.name.as_str()).collect();
+        assert_eq!(failed,vec!["Dinnerbone","Legacy","Broken"]);
+        assert!(matches!(report.failed[0].error,ImportError::Expired));
+        assert!(matches!(&report.failed[1].error,ImportError::Unsupported(x) if x == "Mojang"));
+        assert!(matches!(report.failed[2].error,ImportError::Parse(_)));
+    }
+
+    #[tokio::test]
+    async fn test_import_official(){
+        let file = json!({
+            "accounts": {
+                "local-1": {
+                    "type": "Xbox",
+                    "accessToken": "official-mc",
+                    "accessTokenExpiresAt": (Local::now() + Duration::hours(12)).to_rfc3339(),
+                    "minecraftProfile": {"id": "069a79f444e94726a5befca90e38aaf5", "name": "Notch"},
+                    "username": "notch@example.com"
+                },
+                "local-2": {
+                    "type": "Xbox",
+                    "accessToken": "official-old",
+                    "accessTokenExpiresAt": (Local::now() - Duration::hours(1)).to_rfc3339(),
+                    "minecraftProfile": {"id": "853c80ef3c3749fdaa49938b674adae6", "name": "jeb_"}
+                }
+            },
+            "activeAccountLocalId": "local-1",
+            "mojangClientToken": "client-token"
+        });
+
+        let path = std::env::temp_dir().join(format!("launcher_accounts_{}.json",uuid::Uuid::new_v4()));
+        tokio::fs::write(&path,file.to_string()).await.unwrap();
+        let report = import_official(&path).await.unwrap();
+        tokio::fs::remove_file(&path).await.unwrap();
+
+        assert_eq!(report.accounts.len(),1);
+        assert_eq!(report.accounts[0].name(),"Notch");
+        assert_eq!(report.accounts[0].access_token(),Some("official-mc"));
+        assert!(!report.accounts[0].is_expired());
+
+        assert_eq!(report.failed.len(),1);
+        assert_eq!(report.failed[0].name,"jeb_");
+        assert!(matches!(report.failed[0].error,ImportError::Expired));
+
+        assert!(matches!(import_official("not_exist.json").await,Err(ImportError::Io(_))));
+    }
+
+    #[tokio::test]
+    async fn test_renew_official(){
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/consumers/oauth2/v2.0/token"))
+            .respond_with(ResponseTemplate::new(400))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let file = json!({
+            "accounts": {
+                "local-1": {
+                    "type": "Xbox",
+                    "accessToken": "official-mc",
+                    "accessTokenExpiresAt": (Local::now() + Duration::hours(12)).to_rfc3339(),
+                    "minecraftProfile": {"id": "069a79f444e94726a5befca90e38aaf5", "name": "Notch"}
+                }
+            }
+        });
+
+        let path = std::env::temp_dir().join(format!("launcher_accounts_{}.json",uuid::Uuid::new_v4()));
+        tokio::fs::write(&path,file.to_string()).await.unwrap();
+        let mut report = import_official(&path).await.unwrap();
+        tokio::fs::remove_file(&path).await.unwrap();
+
+        // there's no refresh token, so the refresh fails without sending the request.
+        let Some(AccountKind::Microsoft(mut account)) = report.accounts.pop() else { unreachable!() };
+        let err = account.data.renew(&mock::context(&server),mock::CLIENT_ID).await.unwrap_err();
+        assert!(err.needs_relogin());
+    }
+
+}
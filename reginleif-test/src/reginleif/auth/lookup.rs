@@ -0,0 +1,149 @@
+#[cfg(test)]
+mod test{
+    use std::time::Duration;
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD;
+    use reginleif::auth::error::AuthError;
+    use reginleif::auth::minecraft::SkinVariant;
+    use reginleif::auth::minecraft::lookup::ProfileLookup;
+    use reqwest::StatusCode;
+    use serde_json::json;
+    use wiremock::matchers::{body_json, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use super::super::mock;
+
+    const NOTCH:&str = "069a79f444e94726a5befca90e38aaf5";
+
+    fn session_json() -> serde_json::Value{
+        let textures = json!({
+            "timestamp": 1700000000000i64,
+            "profileId": NOTCH,
+            "profileName": "Notch",
+            "textures": {
+                "SKIN": {
+                    "url": "http://textures.minecraft.net/texture/mock",
+                    "metadata": {"model": "slim"}
+                }
+            }
+        });
+        json!({
+            "id": NOTCH,
+            "name": "Notch",
+            "properties": [{
+                "name": "textures",
+                "value": STANDARD.encode(textures.to_string()),
+                "signature": "mock-signature"
+            }],
+            "profileActions": []
+        })
+    }
+
+    #[tokio::test]
+    async fn test_lookup(){
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/users/profiles/minecraft/notch"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": NOTCH, "name": "Notch"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/users/profiles/minecraft/nobody"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/session/minecraft/profile/{NOTCH}")))
+            .and(query_param("unsigned","false"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(session_json()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let lookup = ProfileLookup::new(mock::context(&server));
+
+        for _ in 0..2 { // the second time is from the cache.
+            let id = lookup.uuid_of("notch").await.unwrap().unwrap();
+            assert_eq!(id.id,NOTCH);
+            assert_eq!(id.name,"Notch");
+            assert!(lookup.uuid_of("nobody").await.unwrap().is_none());
+        }
+
+        let profile = lookup.profile("069a79f4-44e9-4726-a5be-fca90e38aaf5").await.unwrap().unwrap();
+        assert_eq!(profile.properties[0].signature.as_deref(),Some("mock-signature"));
+        let textures = profile.textures().unwrap().unwrap();
+        assert_eq!(textures.profile_name,"Notch");
+        let skin = textures.textures.skin.unwrap();
+        assert_eq!(skin.url,"http://textures.minecraft.net/texture/mock");
+        assert_eq!(skin.variant(),SkinVariant::Slim);
+        assert!(textures.textures.cape.is_none());
+
+        assert_eq!(lookup.profile_of("Notch").await.unwrap().unwrap(),profile); // both from the cache
+
+        // never sent, or they would hit the other endpoints.
+        assert!(lookup.uuid_of("../../minecraft/profile").await.unwrap().is_none());
+        assert!(lookup.uuid_of("no").await.unwrap().is_none());
+        assert!(lookup.profile("069a79f444e94726a5befca90e38aaf5?unsigned=true").await.unwrap().is_none());
+        assert!(lookup.profile("not-a-uuid").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_bulk_lookup(){
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/minecraft/profile/lookup/bulk/byname"))
+            .and(body_json(json!(["jeb_","nobody"])))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{"id": "853c80ef3c3749fdaa49938b674adae6", "name": "jeb_"}])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/users/profiles/minecraft/Notch"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After","10"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/users/profiles/minecraft/Dinnerbone"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&server)
+            .await;
+
+        let lookup = ProfileLookup::new(mock::context(&server))
+            .with_ttl(Duration::from_secs(60));
+
+        let ids = lookup.uuids_of(&["jeb_","nobody","JEB_","not a name","jeb_"]).await.unwrap();
+        assert_eq!(ids.len(),1);
+        assert_eq!(ids[0].name,"jeb_");
+
+        // from the cache
+        assert_eq!(lookup.uuid_of("JEB_").await.unwrap().unwrap().name,"jeb_");
+        assert!(lookup.uuid_of("nobody").await.unwrap().is_none());
+        assert_eq!(lookup.uuids_of(&["jeb_","Jeb_"]).await.unwrap(),ids);
+
+        let err = lookup.uuid_of("Notch").await.unwrap_err();
+        assert!(err.is_retryable());
+
+        // the lookup doesn't use the access token, so 403 isn't a token error.
+        let err = lookup.uuid_of("Dinnerbone").await.unwrap_err();
+        assert!(matches!(err,AuthError::Http{status:StatusCode::FORBIDDEN,..}));
+    }
+
+    #[tokio::test]
+    async fn test_cache_expired(){
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/users/profiles/minecraft/jeb_"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "853c80ef3c3749fdaa49938b674adae6", "name": "jeb_"})))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let lookup = ProfileLookup::new(mock::context(&server))
+            .with_ttl(Duration::ZERO);
+
+        assert!(lookup.uuid_of("jeb_").await.unwrap().is_some());
+        assert!(lookup.uuid_of("jeb_").await.unwrap().is_some()); // expired at once, so fetched again.
+    }
+
+}
@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod test{
+    use std::path::PathBuf;
+    use std::time::Duration;
+    use reginleif::auth::account::{Account, AccountKind};
+    use reginleif::auth::manager::AccountManager;
+    use reginleif::auth::offline::OfflineAccount;
+    use reginleif_macro::BaseStorePoint;
+    use reginleif_utils::encrypted_store::{EncryptedStore, StoreKey};
+    use reginleif_utils::save_path::Store;
+    use wiremock::MockServer;
+    use super::super::mock;
+
+    #[derive(BaseStorePoint,Clone,Debug)]
+    struct TestPath(PathBuf);
+
+    fn account(refresh_token:&str) -> Account{
+        let mut account = mock::account();
+        account.msa.data.expires_in = Duration::ZERO;
+        account.msa.data.refresh_token = refresh_token.into();
+        account.mc_auth.expires_in = Duration::ZERO;
+        account.mc_auth.access_token = "old-mc-access".into();
+        account
+    }
+
+    #[test]
+    fn test_manage_accounts(){
+        let mut manager = AccountManager::<TestPath>::new();
+        assert!(manager.selected().is_none());
+
+        manager.add(OfflineAccount::new("Alice"));
+        manager.add(OfflineAccount::new("Bob"));
+        assert_eq!(manager.len(),2);
+        assert_eq!(manager.selected().unwrap().name(),"Alice"); // the first one is selected.
+
+        let bob = OfflineAccount::uuid_of("Bob");
+        assert!(manager.select(&bob));
+        assert!(!manager.select("not-exist"));
+        assert_eq!(manager.selected().unwrap().name(),"Bob");
+
+        let old = manager.add(OfflineAccount::new("Bob"));
+        assert!(old.is_some());
+        assert_eq!(manager.len(),2);
+
+        assert!(manager.remove(&bob).is_some());
+        assert!(manager.selected().is_none());
+        assert!(manager.get(&OfflineAccount::uuid_of("Alice")).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_persist_and_refresh_all(){
+        let path = PathBuf::from("test_account_manager");
+        let base = TestPath(path.clone());
+
+        let mut manager = AccountManager::load_or_default(&base).unwrap();
+        assert!(manager.is_empty());
+        manager.add(account("msa-refresh"));
+        manager.add(OfflineAccount::new("Alice"));
+        manager.save(&base).unwrap();
+
+        let mut manager = AccountManager::<TestPath>::load(&base).unwrap();
+        tokio::fs::remove_dir_all(path).await.unwrap();
+        assert_eq!(manager.len(),2);
+        assert_eq!(manager.selected().unwrap().uuid(),"069a79f444e94726a5befca90e38aaf5");
+
+        let server = MockServer::start().await;
+        mock::mount_token(&server).await;
+        mock::mount_xbox_minecraft(&server).await;
+
+        let failed = manager.refresh_all(&mock::context(&server),mock::CLIENT_ID).await;
+        assert!(failed.is_empty());
+        assert!(matches!(manager.selected(),Some(AccountKind::Microsoft(x)) if x.data.mc_auth.access_token == "mc-access"));
+
+        let server = MockServer::start().await;
+        mock::mount_token_error(&server,"invalid_grant",1).await;
+        manager.add(account("revoked"));
+        let failed = manager.refresh_all(&mock::context(&server),mock::CLIENT_ID).await;
+        assert_eq!(failed.len(),1);
+        assert_eq!(failed[0].0,"069a79f444e94726a5befca90e38aaf5");
+        assert!(failed[0].1.needs_relogin());
+    }
+
+    #[test]
+    fn test_load_or_default(){
+        let path = PathBuf::from("test_account_manager_load");
+        let base = TestPath(path.clone());
+        let key = StoreKey::passphrase("passphrase");
+
+        assert!(AccountManager::load_or_default_encrypted(&base,&key).unwrap().is_empty());
+
+        let mut manager = AccountManager::new();
+        manager.add(OfflineAccount::new("Alice"));
+        manager.save_encrypted(&base,&key).unwrap();
+        assert_eq!(AccountManager::load_or_default_encrypted(&base,&key).unwrap().len(),1);
+        assert!(AccountManager::load_or_default_encrypted(&base,&StoreKey::passphrase("wrong")).is_err());
+
+        // the broken file is not replaced by an empty manager.
+        std::fs::write(path.join("accounts.json"),"{broken").unwrap();
+        assert!(AccountManager::load_or_default(&base).is_err());
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+}
@@ -0,0 +1,94 @@
+#[cfg(test)]
+mod test{
+    use std::time::Duration;
+    use reginleif::auth::microsoft::{MicrosoftAuth, MicrosoftAuthError, TokenRotation};
+    use reginleif_utils::expiring_data::ExpiringData;
+    use serde_json::json;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use super::super::mock;
+
+    fn msa() -> MicrosoftAuth{
+        MicrosoftAuth{
+            token_type: "Bearer".to_string(),
+            scope: "XboxLive.signin".to_string(),
+            expires_in: Duration::from_secs(0),
+            access_token: "old-access".into(),
+            refresh_token: "old-refresh".into(),
+        }
+    }
+
+    async fn mount_refresh(server:&MockServer,body:serde_json::Value){
+        Mock::given(method("POST"))
+            .and(path("/consumers/oauth2/v2.0/token"))
+            .and(body_string_contains("grant_type=refresh_token"))
+            .and(body_string_contains("refresh_token=old-refresh"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_renew_rotated(){
+        let server = MockServer::start().await;
+        mount_refresh(&server,mock::microsoft_auth_json("new-access","new-refresh")).await;
+
+        let mut msa = msa();
+        let rotation = msa.renew(&mock::context(&server),mock::CLIENT_ID).await.unwrap();
+
+        assert_eq!(rotation,TokenRotation::Rotated);
+        assert_eq!(msa.access_token,"new-access");
+        assert_eq!(msa.refresh_token,"new-refresh");
+        assert_eq!(msa.scope,"XboxLive.signin offline_access");
+        assert_eq!(msa.expires_in,Duration::from_secs(3600));
+    }
+
+    #[tokio::test]
+    async fn test_renew_unchanged(){
+        let server = MockServer::start().await;
+        mount_refresh(&server,json!({
+            "token_type": "Bearer",
+            "expires_in": 3600,
+            "access_token": "new-access"
+        })).await;
+
+        let mut msa = msa();
+        let rotation = msa.renew(&mock::context(&server),mock::CLIENT_ID).await.unwrap();
+
+        assert_eq!(rotation,TokenRotation::Unchanged);
+        assert_eq!(msa.access_token,"new-access");
+        assert_eq!(msa.refresh_token,"old-refresh");
+        assert_eq!(msa.scope,"XboxLive.signin");
+    }
+
+    #[tokio::test]
+    async fn test_refresh_expiring_data(){
+        let server = MockServer::start().await;
+        mount_refresh(&server,mock::microsoft_auth_json("new-access","new-refresh")).await;
+
+        let mut msa:ExpiringData<_> = msa().into();
+        assert!(msa.is_expired());
+        let args = (mock::context(&server),mock::CLIENT_ID.to_string());
+        let data = msa.try_ref(&args).await.unwrap();
+
+        assert_eq!(data.refresh_token,"new-refresh");
+        assert!(!msa.is_expired());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_revoked(){
+        let server = MockServer::start().await;
+        mock::mount_token_error(&server,"invalid_grant",2).await;
+
+        let mut msa = msa();
+        let err = msa.renew(&mock::context(&server),mock::CLIENT_ID).await.unwrap_err();
+        assert!(matches!(err,MicrosoftAuthError::InvalidGrant));
+        assert_eq!(msa.refresh_token,"old-refresh");
+
+        let mut msa:ExpiringData<_> = msa.into();
+        let args = (mock::context(&server),mock::CLIENT_ID.to_string());
+        let err = msa.refresh(&args).await.unwrap_err();
+        assert!(matches!(err.downcast_ref(),Some(MicrosoftAuthError::InvalidGrant)));
+    }
+
+}
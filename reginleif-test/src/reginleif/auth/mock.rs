@@ -0,0 +1,212 @@
+//! A mock of the whole auth chain, so the auth flow can be tested without hitting production.
+
+use std::time::Duration;
+use reginleif::auth::account::Account;
+use reginleif::auth::endpoint::AuthEndpoints;
+use reginleif::auth::microsoft::MicrosoftAuth;
+use reginleif::auth::minecraft::{MinecraftAuth, Profile};
+use reginleif::auth::rate_limit::RateLimitConfig;
+use reginleif::http::HttpContext;
+use serde_json::json;
+use wiremock::matchers::{body_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+pub const CLIENT_ID: &str = "mock-client-id";
+
+/// Build the endpoints which point to the mock server.
+pub fn endpoints(server: &MockServer) -> AuthEndpoints {
+    let uri = server.uri();
+    AuthEndpoints {
+        authorize: format!("{uri}/consumers/oauth2/v2.0/authorize"),
+        device_code: format!("{uri}/consumers/oauth2/v2.0/devicecode"),
+        token: format!("{uri}/consumers/oauth2/v2.0/token"),
+        xbox_user_authenticate: format!("{uri}/user/authenticate"),
+        xbox_xsts_authorize: format!("{uri}/xsts/authorize"),
+        xbox_profile: format!("{uri}/users/me/profile/settings"),
+        minecraft_login_with_xbox: format!("{uri}/authentication/login_with_xbox"),
+        minecraft_profile: format!("{uri}/minecraft/profile"),
+        minecraft_entitlements: format!("{uri}/entitlements"),
+        minecraft_player: format!("{uri}/player"),
+        mojang_name_lookup: format!("{uri}/users/profiles/minecraft"),
+        minecraft_bulk_lookup: format!("{uri}/minecraft/profile/lookup/bulk/byname"),
+        session_profile: format!("{uri}/session/minecraft/profile"),
+    }
+}
+
+/// Build the HTTP context with [endpoints], the limiter refills almost immediately to keep the tests fast.
+pub fn context(server: &MockServer) -> HttpContext {
+    HttpContext::builder()
+        .endpoints(endpoints(server))
+        .rate_limit(RateLimitConfig{
+            refill_interval: Duration::from_millis(1),
+            ..RateLimitConfig::default()
+        })
+        .build()
+        .unwrap()
+}
+
+/// The Microsoft Auth which [mount_xbox_minecraft] accepts, it expires in an hour.
+pub fn microsoft_auth() -> MicrosoftAuth {
+    MicrosoftAuth {
+        token_type: "Bearer".to_string(),
+        scope: "XboxLive.signin offline_access".to_string(),
+        expires_in: Duration::from_secs(3600),
+        access_token: "msa-access".into(),
+        refresh_token: "msa-refresh".into(),
+    }
+}
+
+/// The Minecraft Auth which [mount_xbox_minecraft] accepts, it expires in a day.
+pub fn minecraft_auth() -> MinecraftAuth {
+    MinecraftAuth {
+        username: "mock-username".to_string(),
+        access_token: "mc-access".into(),
+        expires_in: Duration::from_secs(86400),
+        token_type: "Bearer".to_string(),
+    }
+}
+
+/// The account of Notch with [minecraft_auth], [microsoft_auth] and [profile_json].
+pub fn account() -> Account {
+    let profile = serde_json::from_value::<Profile>(profile_json()).unwrap();
+    (minecraft_auth(), profile, microsoft_auth().into()).into()
+}
+
+pub fn device_code_json() -> serde_json::Value {
+    json!({
+        "user_code": "MOCKCODE",
+        "device_code": "mock-device-code",
+        "verification_uri": "https://www.microsoft.com/link",
+        "expires_in": 900,
+        "interval": 0
+    })
+}
+
+pub fn microsoft_auth_json(access_token: &str, refresh_token: &str) -> serde_json::Value {
+    json!({
+        "token_type": "Bearer",
+        "scope": "XboxLive.signin offline_access",
+        "expires_in": 3600,
+        "access_token": access_token,
+        "refresh_token": refresh_token
+    })
+}
+
+pub fn profile_json() -> serde_json::Value {
+    json!({
+        "id": "069a79f444e94726a5befca90e38aaf5",
+        "name": "Notch",
+        "skins": [{
+            "id": "skin-id",
+            "state": "ACTIVE",
+            "url": "http://textures.minecraft.net/texture/mock",
+            "textureKey": "mock",
+            "variant": "CLASSIC"
+        }],
+        "capes": []
+    })
+}
+
+/// Mount the device code endpoint and a token endpoint which accept the device code at once.
+pub async fn mount_microsoft(server: &MockServer) {
+    mount_device_code(server).await;
+    mount_token(server).await;
+}
+
+pub async fn mount_device_code(server: &MockServer) {
+    Mock::given(method("POST"))
+        .and(path("/consumers/oauth2/v2.0/devicecode"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(device_code_json()))
+        .mount(server)
+        .await;
+}
+
+/// Mount a token endpoint which responds the given OAuth error code `times` times.
+pub async fn mount_token_error(server: &MockServer, error: &str, times: u64) {
+    Mock::given(method("POST"))
+        .and(path("/consumers/oauth2/v2.0/token"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+            "error": error,
+            "error_description": format!("mock {error}")
+        })))
+        .up_to_n_times(times)
+        .mount(server)
+        .await;
+}
+
+pub async fn mount_token(server: &MockServer) {
+    Mock::given(method("POST"))
+        .and(path("/consumers/oauth2/v2.0/token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(microsoft_auth_json("msa-access", "msa-refresh")))
+        .mount(server)
+        .await;
+}
+
+/// Mount the Xbox and Minecraft part of the chain.
+pub async fn mount_xbox_minecraft(server: &MockServer) {
+    Mock::given(method("POST"))
+        .and(path("/user/authenticate"))
+        .and(body_json(json!({
+            "Properties": {
+                "AuthMethod": "RPS",
+                "SiteName": "user.auth.xboxlive.com",
+                "RpsTicket": "d=msa-access"
+            },
+            "RelyingParty": "http://auth.xboxlive.com",
+            "TokenType": "JWT"
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "Token": "xbl-token",
+            "DisplayClaims": {"xui": [{"uhs": "mock-uhs"}]}
+        })))
+        .mount(server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/xsts/authorize"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "Token": "xsts-token",
+            "DisplayClaims": {"xui": [{"uhs": "mock-uhs"}]}
+        })))
+        .mount(server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/authentication/login_with_xbox"))
+        .and(body_json(json!({"identityToken": "XBL3.0 x=mock-uhs;xsts-token"})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "username": "mock-username",
+            "access_token": "mc-access",
+            "expires_in": 86400,
+            "token_type": "Bearer"
+        })))
+        .mount(server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/minecraft/profile"))
+        .and(header("Authorization", "Bearer mc-access"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(profile_json()))
+        .mount(server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/entitlements/license"))
+        .and(header("Authorization", "Bearer mc-access"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(entitlements_json(&[("product_minecraft", "PURCHASE")])))
+        .mount(server)
+        .await;
+}
+
+/// The response of entitlements endpoint with the given `(name, source)` items.
+pub fn entitlements_json(items: &[(&str, &str)]) -> serde_json::Value {
+    let items: Vec<_> = items.iter()
+        .map(|(name, source)| json!({"name": name, "source": source, "signature": "item-jwt"}))
+        .collect();
+    json!({
+        "items": items,
+        "signature": "mock-jwt",
+        "keyId": "1",
+        "requestId": "mock-request"
+    })
+}
@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod test{
+    use reginleif::auth::account::{Account, AccountSetup};
+    use reginleif::auth::error::{AuthError, MinecraftServicesError};
+    use reginleif::auth::minecraft::entitlement::GameOwnership;
+    use reginleif::auth::minecraft::name::NameAvailability;
+    use reginleif::auth::minecraft::Profile;
+    use serde_json::json;
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use super::super::mock;
+
+    async fn mount_available(server:&MockServer,name:&str,status:&str){
+        Mock::given(method("GET"))
+            .and(path(format!("/minecraft/profile/name/{name}/available")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"status": status})))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_first_login(){
+        let server = MockServer::start().await;
+        mock::mount_xbox_minecraft(&server).await;
+        Mock::given(method("GET"))
+            .and(path("/minecraft/profile"))
+            .respond_with(ResponseTemplate::new(404))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        mount_available(&server,"Notch","AVAILABLE").await;
+        mount_available(&server,"jeb_","DUPLICATE").await;
+        Mock::given(method("POST"))
+            .and(path("/minecraft/profile"))
+            .and(body_json(json!({"profileName": "jeb_"})))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "path": "/minecraft/profile",
+                "error": "FORBIDDEN",
+                "details": {"status": "DUPLICATE"}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/minecraft/profile"))
+            .and(body_json(json!({"profileName": "Notch"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(mock::profile_json()))
+            .mount(&server)
+            .await;
+
+        let ctx = mock::context(&server);
+
+        let setup = match Account::from_msa(&ctx,mock::microsoft_auth().into()).await.unwrap() {
+            AccountSetup::NeedsProfile(setup) => setup,
+            AccountSetup::Ready(_) => panic!("the profile should not exist"),
+        };
+        assert_eq!(setup.ownership,GameOwnership::Purchased);
+
+        assert_eq!(setup.check_name(&ctx,"jeb_").await.unwrap(),NameAvailability::Duplicate);
+        let err = setup.create(&ctx,"jeb_").await.unwrap_err();
+        assert!(matches!(err,AuthError::Minecraft(MinecraftServicesError::NameUnavailable(NameAvailability::Duplicate))));
+
+        assert!(setup.check_name(&ctx,"Notch").await.unwrap().is_available());
+        assert_eq!(setup.check_name(&ctx,"../../namechange").await.unwrap(),NameAvailability::NotAllowed);
+        let account = setup.create(&ctx,"Notch").await.unwrap();
+        assert_eq!(account.profile.name,"Notch");
+        assert_eq!(account.msa.data.refresh_token,"msa-refresh");
+    }
+
+    #[tokio::test]
+    async fn test_change_name(){
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/minecraft/profile/namechange"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "changedAt": "2019-02-04T02:34:23Z",
+                "createdAt": "2010-01-01T00:00:00Z",
+                "nameChangeAllowed": true
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/minecraft/profile/name/Notch"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(mock::profile_json()))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/minecraft/profile/name/a"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "details": {"status": "NOT_ALLOWED"}
+            })))
+            .expect(0) // checked before sending.
+            .mount(&server)
+            .await;
+
+        let ctx = mock::context(&server);
+
+        let info = Profile::name_change_info(&ctx,&mock::minecraft_auth()).await.unwrap();
+        assert!(info.name_change_allowed);
+        assert!(info.changed_at.is_some());
+
+        let mut account = mock::account();
+        account.profile.name = "Old".to_string();
+
+        let err = account.change_name(&ctx,"a").await.unwrap_err();
+        assert!(matches!(err,AuthError::Minecraft(MinecraftServicesError::NameUnavailable(NameAvailability::NotAllowed))));
+        assert_eq!(account.profile.name,"Old");
+
+        account.change_name(&ctx,"Notch").await.unwrap();
+        assert_eq!(account.profile.name,"Notch");
+    }
+
+    #[tokio::test]
+    async fn test_malformed_name_change_info(){
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/minecraft/profile/namechange"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "changedAt": "2019-02-04T02:34:23Z",
+                "createdAt": "yesterday",
+                "nameChangeAllowed": true
+            })))
+            .mount(&server)
+            .await;
+
+        let err = Profile::name_change_info(&mock::context(&server),&mock::minecraft_auth()).await.unwrap_err();
+        assert!(matches!(err,AuthError::InvalidResponse(_)));
+    }
+
+}
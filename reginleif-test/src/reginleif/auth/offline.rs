@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod test{
+    use std::time::Duration;
+    use reginleif::auth::account::AccountKind;
+    use reginleif::auth::offline::OfflineAccount;
+    use wiremock::MockServer;
+    use super::super::mock;
+
+    #[test]
+    fn test_offline_uuid(){
+        // same as UUID.nameUUIDFromBytes("OfflinePlayer:<name>") in Java.
+        assert_eq!(OfflineAccount::uuid_of("Notch"),"b50ad385829d3141a2167e7d7539ba7f");
+        assert_eq!(OfflineAccount::uuid_of("jeb_"),OfflineAccount::new("jeb_").uuid);
+        assert_ne!(OfflineAccount::uuid_of("Notch"),OfflineAccount::uuid_of("notch"));
+
+        let uuid = uuid::Uuid::parse_str(&OfflineAccount::uuid_of("Notch")).unwrap();
+        assert_eq!(uuid.get_version_num(),3);
+    }
+
+    #[tokio::test]
+    async fn test_account_kind(){
+        let server = MockServer::start().await;
+        let ctx = mock::context(&server);
+
+        let mut offline:AccountKind = OfflineAccount::new("Notch").into();
+        assert!(!offline.is_expired());
+        assert_eq!(offline.access_token(),None);
+        assert_eq!(offline.user_type(),"legacy");
+        offline.ensure_fresh(&ctx,mock::CLIENT_ID).await.unwrap(); // no request is sent.
+
+        let json = serde_json::to_string(&offline).unwrap();
+        let offline:AccountKind = serde_json::from_str(&json).unwrap();
+        assert!(matches!(&offline,AccountKind::Offline(x) if x.name == "Notch"));
+
+        let mut account = mock::account();
+        account.mc_auth.expires_in = Duration::ZERO;
+        let mut online:AccountKind = account.into();
+        assert!(online.is_expired());
+
+        mock::mount_xbox_minecraft(&server).await;
+        online.ensure_fresh(&ctx,mock::CLIENT_ID).await.unwrap();
+        assert!(!online.is_expired());
+        assert_eq!(online.uuid(),"069a79f444e94726a5befca90e38aaf5");
+        assert_eq!(online.access_token(),Some("mc-access"));
+        assert_eq!(online.user_type(),"msa");
+    }
+
+}
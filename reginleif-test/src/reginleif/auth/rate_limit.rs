@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod test{
+    use std::time::{Duration, Instant};
+    use chrono::Utc;
+    use reginleif::auth::error::{AuthError, MinecraftServicesError};
+    use reginleif::auth::minecraft::Profile;
+    use reginleif::auth::rate_limit::{RateLimitConfig, RateLimiter};
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use super::super::mock;
+
+    #[tokio::test]
+    async fn test_retry_after(){
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/minecraft/profile"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After","1"))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/minecraft/profile"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(mock::profile_json()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let start = Instant::now();
+        let profile = Profile::fetch(&mock::context(&server),&mock::minecraft_auth()).await.unwrap();
+        assert_eq!(profile.name,"Notch");
+        assert!(start.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_date(){
+        let server = MockServer::start().await;
+        let date = (Utc::now() + chrono::Duration::seconds(60)).format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+        Mock::given(method("GET"))
+            .and(path("/minecraft/profile"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After",date.as_str()))
+            .expect(1) // longer than max_wait, so it's not retried.
+            .mount(&server)
+            .await;
+
+        let err = Profile::fetch(&mock::context(&server),&mock::minecraft_auth()).await.unwrap_err();
+        match err {
+            AuthError::Minecraft(MinecraftServicesError::TooManyRequests{retry_after:Some(x)}) => {
+                assert!(x > Duration::from_secs(55) && x <= Duration::from_secs(60));
+            }
+            other => panic!("unexpected error: {:?}",other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backoff_gives_up(){
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(429))
+            .expect(3) // the first try and 2 retries
+            .mount(&server)
+            .await;
+
+        let limiter = RateLimiter::new(RateLimitConfig{
+            max_retries: 2,
+            min_backoff: Duration::from_millis(10),
+            ..RateLimitConfig::default()
+        });
+
+        let res = limiter.send(reqwest::Client::new().get(server.uri())).await.unwrap();
+        assert_eq!(res.status().as_u16(),429);
+    }
+
+    #[tokio::test]
+    async fn test_token_bucket(){
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(4)
+            .mount(&server)
+            .await;
+
+        let limiter = RateLimiter::new(RateLimitConfig{
+            capacity: 2,
+            refill_interval: Duration::from_millis(200),
+            ..RateLimitConfig::default()
+        });
+        let client = reqwest::Client::new();
+
+        let start = Instant::now();
+        for _ in 0..2 {
+            limiter.send(client.get(server.uri())).await.unwrap();
+        }
+        assert!(start.elapsed() < Duration::from_millis(200));
+
+        for _ in 0..2 { // wait for the refill
+            limiter.send(client.get(server.uri())).await.unwrap();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(400));
+    }
+
+}
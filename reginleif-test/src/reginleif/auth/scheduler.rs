@@ -0,0 +1,254 @@
+#[cfg(test)]
+mod test{
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::Mutex;
+    use reginleif::auth::account::{Account, AccountKind};
+    use reginleif::auth::offline::OfflineAccount;
+    use reginleif::auth::scheduler::{RefreshConfig, RefreshEvent, RefreshScheduler, SharedAccount};
+    use reginleif::auth::yggdrasil::{YggdrasilAccount, YggdrasilProfile, YggdrasilServer};
+    use reginleif_utils::expiring_data::ExpiringData;
+    use serde_json::json;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use super::super::mock;
+
+    const UUID: &str = "069a79f444e94726a5befca90e38aaf5";
+
+    fn config() -> RefreshConfig{
+        RefreshConfig{
+            margin: Duration::from_secs(60),
+            min_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(50),
+            max_retries: 2,
+            validate_interval: Duration::from_millis(100),
+        }
+    }
+
+    fn account(msa_expires_in:u64) -> SharedAccount{
+        let mut account = mock::account();
+        account.msa.data.expires_in = Duration::from_secs(msa_expires_in);
+        account.msa.data.refresh_token = "revoked".into();
+        account.mc_auth.access_token = "old-mc-access".into();
+        account.mc_auth.expires_in = Duration::from_secs(30); // less than the margin, so it's refreshed at once.
+        Arc::new(Mutex::new(account.into()))
+    }
+
+    fn microsoft(account:&mut AccountKind) -> &mut ExpiringData<Account>{
+        match account {
+            AccountKind::Microsoft(x) => x,
+            other => panic!("unexpected account: {:?}",other),
+        }
+    }
+
+    async fn next(events:&mut tokio::sync::broadcast::Receiver<RefreshEvent>) -> RefreshEvent{
+        tokio::time::timeout(Duration::from_secs(5),events.recv()).await.unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_refresh_with_retry(){
+        let server = MockServer::start().await;
+        mock::mount_xbox_minecraft(&server).await;
+        Mock::given(method("POST"))
+            .and(path("/user/authenticate"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+
+        let scheduler = RefreshScheduler::new(mock::context(&server),mock::CLIENT_ID,config());
+        let mut events = scheduler.subscribe();
+        let account = account(3600);
+        scheduler.register(account.clone()).await;
+
+        match next(&mut events).await {
+            RefreshEvent::RetryScheduled{uuid,attempt,retry_in,error} => {
+                assert_eq!(uuid,UUID);
+                assert_eq!(attempt,1);
+                assert_eq!(retry_in,Duration::from_millis(10));
+                assert!(error.is_retryable());
+            }
+            other => panic!("unexpected event: {:?}",other),
+        }
+        match next(&mut events).await {
+            RefreshEvent::Refreshed{uuid,account} => {
+                assert_eq!(uuid,UUID);
+                assert_eq!(account.access_token(),Some("mc-access"));
+            }
+            other => panic!("unexpected event: {:?}",other),
+        }
+        assert_eq!(microsoft(&mut *account.lock().await).data.mc_auth.access_token,"mc-access");
+        assert!(microsoft(&mut *account.lock().await).expire_in() > Duration::from_secs(3600));
+
+        assert!(scheduler.unregister(UUID));
+        assert!(!scheduler.unregister(UUID));
+    }
+
+    #[tokio::test]
+    async fn test_needs_relogin(){
+        let server = MockServer::start().await;
+        mock::mount_token_error(&server,"invalid_grant",1).await;
+
+        let scheduler = RefreshScheduler::new(mock::context(&server),mock::CLIENT_ID,config());
+        let mut events = scheduler.subscribe();
+        scheduler.register(account(0)).await; // msa is expired, so the refresh token is used.
+
+        match next(&mut events).await {
+            RefreshEvent::NeedsRelogin{uuid,error} => {
+                assert_eq!(uuid,UUID);
+                assert!(error.needs_relogin());
+            }
+            other => panic!("unexpected event: {:?}",other),
+        }
+        assert!(!scheduler.unregister(UUID)); // stopped watching.
+    }
+
+    #[tokio::test]
+    async fn test_account_unlocked_during_refresh(){
+        let server = MockServer::start().await;
+        mock::mount_xbox_minecraft(&server).await;
+        Mock::given(method("POST"))
+            .and(path("/user/authenticate"))
+            .respond_with(ResponseTemplate::new(503).set_delay(Duration::from_millis(500)))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+
+        let config = RefreshConfig{ min_backoff: Duration::from_millis(300), ..config() };
+        let scheduler = RefreshScheduler::new(mock::context(&server),mock::CLIENT_ID,config);
+        let mut events = scheduler.subscribe();
+        let account = account(3600);
+        scheduler.register(account.clone()).await;
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(account.try_lock().is_ok());
+
+        // refreshed by the launcher during the retry, so the scheduler waits again.
+        assert!(matches!(next(&mut events).await,RefreshEvent::RetryScheduled{..}));
+        microsoft(&mut *account.lock().await).data.mc_auth.expires_in = Duration::from_secs(3600);
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(microsoft(&mut *account.lock().await).data.mc_auth.access_token,"old-mc-access");
+    }
+
+    #[tokio::test]
+    async fn test_keep_launcher_changes(){
+        let server = MockServer::start().await;
+        mock::mount_xbox_minecraft(&server).await;
+        Mock::given(method("POST"))
+            .and(path("/authentication/login_with_xbox"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "username": "mock-username",
+                "access_token": "mc-access",
+                "expires_in": 86400,
+                "token_type": "Bearer"
+            })).set_delay(Duration::from_millis(500)))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+
+        let scheduler = RefreshScheduler::new(mock::context(&server),mock::CLIENT_ID,config());
+        let mut events = scheduler.subscribe();
+        let account = account(3600);
+        scheduler.register(account.clone()).await;
+
+        // the user logs in again during the renewal.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        {
+            let mut account = account.lock().await;
+            let account = microsoft(&mut account);
+            account.data.msa.data.refresh_token = "relogin-refresh".into();
+            account.data.mc_auth.access_token = "relogin-mc-access".into();
+            account.data.mc_auth.expires_in = Duration::from_secs(3600);
+        }
+
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        assert_eq!(microsoft(&mut *account.lock().await).data.mc_auth.access_token,"relogin-mc-access");
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_yggdrasil_and_offline(){
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/authserver/validate"))
+            .and(body_partial_json(json!({"accessToken": "ygg-old"})))
+            .respond_with(ResponseTemplate::new(403).set_body_json(json!({
+                "error": "ForbiddenOperationException",
+                "errorMessage": "Invalid token."
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/authserver/refresh"))
+            .and(body_partial_json(json!({"accessToken": "ygg-old"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "accessToken": "ygg-new",
+                "clientToken": "ygg-client"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/authserver/validate"))
+            .and(body_partial_json(json!({"accessToken": "ygg-new"})))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1..)
+            .mount(&server)
+            .await;
+
+        let scheduler = RefreshScheduler::new(mock::context(&server),mock::CLIENT_ID,config());
+        let mut events = scheduler.subscribe();
+
+        let account = YggdrasilAccount{
+            server: YggdrasilServer{ root: server.uri() },
+            access_token: "ygg-old".into(),
+            client_token: "ygg-client".to_string(),
+            profile: YggdrasilProfile{ id: "Bob-uuid".to_string(), name: "Bob".to_string() },
+            prefetched: None,
+        };
+        let account:SharedAccount = Arc::new(Mutex::new(account.into()));
+        scheduler.register(account.clone()).await;
+
+        match next(&mut events).await {
+            RefreshEvent::Refreshed{uuid,account} => {
+                assert_eq!(uuid,"Bob-uuid");
+                assert_eq!(account.access_token(),Some("ygg-new"));
+            }
+            other => panic!("unexpected event: {:?}",other),
+        }
+        assert_eq!(account.lock().await.access_token(),Some("ygg-new"));
+
+        // the valid token is checked again later, but nothing is refreshed.
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(events.try_recv().is_err());
+        assert!(scheduler.unregister("Bob-uuid"));
+
+        let offline = OfflineAccount::new("Notch");
+        let uuid = offline.uuid.clone();
+        scheduler.register(Arc::new(Mutex::new(offline.into()))).await;
+        assert!(!scheduler.unregister(&uuid)); // never expires, so it's not watched.
+    }
+
+    #[tokio::test]
+    async fn test_give_up(){
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/user/authenticate"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        let scheduler = RefreshScheduler::new(mock::context(&server),mock::CLIENT_ID,config());
+        let mut events = scheduler.subscribe();
+        scheduler.register(account(3600)).await;
+
+        assert!(matches!(next(&mut events).await,RefreshEvent::RetryScheduled{attempt:1,..}));
+        assert!(matches!(next(&mut events).await,RefreshEvent::RetryScheduled{attempt:2,retry_in,..} if retry_in == Duration::from_millis(20)));
+        assert!(matches!(next(&mut events).await,RefreshEvent::Failed{..}));
+        assert!(!scheduler.unregister(UUID));
+    }
+
+}
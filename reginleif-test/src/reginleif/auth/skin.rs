@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod test{
+    use reginleif::auth::minecraft::{Profile, SkinVariant};
+    use serde_json::json;
+    use wiremock::matchers::{body_json, body_string_contains, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use super::super::mock;
+
+    fn profile_json(variant:&str,cape_state:&str) -> serde_json::Value{
+        json!({
+            "id": "069a79f444e94726a5befca90e38aaf5",
+            "name": "Notch",
+            "skins": [{
+                "id": "skin-id",
+                "state": "ACTIVE",
+                "url": "http://textures.minecraft.net/texture/skin",
+                "textureKey": "skin",
+                "variant": variant
+            }],
+            "capes": [{
+                "id": "cape-id",
+                "state": cape_state,
+                "url": "http://textures.minecraft.net/texture/cape",
+                "alias": "Migrator"
+            }]
+        })
+    }
+
+    #[tokio::test]
+    async fn test_change_skin(){
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/minecraft/profile/skins"))
+            .and(header("Authorization","Bearer mc-access"))
+            .and(body_string_contains("slim"))
+            .and(body_string_contains("filename=\"skin.png\""))
+            .and(body_string_contains("mock-png"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(profile_json("SLIM","INACTIVE")))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/minecraft/profile/skins"))
+            .and(body_json(json!({"variant": "classic", "url": "https://example.com/skin.png"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(profile_json("CLASSIC","INACTIVE")))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/minecraft/profile/skins/active"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(profile_json("CLASSIC","INACTIVE")))
+            .mount(&server)
+            .await;
+
+        let ctx = mock::context(&server);
+
+        let profile = Profile::upload_skin(&ctx,&mock::minecraft_auth(),SkinVariant::Slim,b"mock-png".to_vec()).await.unwrap();
+        assert_eq!(profile.active_skin().unwrap().variant,"SLIM");
+
+        let profile = Profile::set_skin_url(&ctx,&mock::minecraft_auth(),SkinVariant::Classic,"https://example.com/skin.png").await.unwrap();
+        assert_eq!(profile.active_skin().unwrap().variant,"CLASSIC");
+
+        let profile = Profile::reset_skin(&ctx,&mock::minecraft_auth()).await.unwrap();
+        assert_eq!(profile.name,"Notch");
+    }
+
+    #[tokio::test]
+    async fn test_change_cape(){
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/minecraft/profile/capes/active"))
+            .and(body_json(json!({"capeId": "cape-id"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(profile_json("CLASSIC","ACTIVE")))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/minecraft/profile/capes/active"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(profile_json("CLASSIC","INACTIVE")))
+            .mount(&server)
+            .await;
+
+        let ctx = mock::context(&server);
+
+        let profile = Profile::show_cape(&ctx,&mock::minecraft_auth(),"cape-id").await.unwrap();
+        assert_eq!(profile.active_cape().unwrap().alias,"Migrator");
+
+        let profile = Profile::hide_cape(&ctx,&mock::minecraft_auth()).await.unwrap();
+        assert!(profile.active_cape().is_none());
+    }
+
+}
@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod test{
+    use std::path::PathBuf;
+    use image::{Rgba, RgbaImage};
+    use reginleif::auth::minecraft::SkinVariant;
+    use reginleif::auth::minecraft::texture::SkinTexture;
+    use reginleif::http::HttpContext;
+    use reginleif_macro::BaseStorePoint;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[derive(BaseStorePoint,Clone,Debug)]
+    struct TestPath(PathBuf);
+
+    const RED:Rgba<u8> = Rgba([255,0,0,255]);
+    const GREEN:Rgba<u8> = Rgba([0,255,0,255]);
+    const BLUE:Rgba<u8> = Rgba([0,0,255,255]);
+
+    /// A skin with red face, green body and blue right arm.
+    fn skin(height:u32,slim:bool) -> RgbaImage{
+        let mut image = RgbaImage::new(64,height);
+        let mut fill = |x:u32,y:u32,w:u32,h:u32,color:Rgba<u8>|{
+            for i in x..x+w { for j in y..y+h { image.put_pixel(i,j,color); } }
+        };
+        fill(8,8,8,8,RED);
+        fill(20,20,8,12,GREEN);
+        fill(40,16,if slim {14} else {16},16,BLUE);
+        fill(41,9,2,2,BLUE); // the hat covers a part of the face
+        image
+    }
+
+    #[test]
+    fn test_skin_texture(){
+        let texture = SkinTexture::new(skin(64,false)).unwrap();
+        assert_eq!(texture.variant(),SkinVariant::Classic);
+        let slim = SkinTexture::new(skin(64,true)).unwrap();
+        assert_eq!(slim.variant(),SkinVariant::Slim);
+        assert!(SkinTexture::new(RgbaImage::new(32,32)).is_err());
+
+        // the left arm is mirrored from the right arm.
+        let legacy = SkinTexture::new(skin(32,false)).unwrap();
+        assert_eq!(legacy.image().dimensions(),(64,64));
+        assert_eq!(*legacy.image().get_pixel(36,52),BLUE);
+        assert_eq!(legacy.variant(),SkinVariant::Classic);
+
+        let head = texture.render_head(32);
+        assert_eq!(head.dimensions(),(32,32));
+        assert_eq!(*head.get_pixel(0,0),RED);
+        assert_eq!(*head.get_pixel(4,4),BLUE); // hat overlay at (1,1) of the face
+
+        let body = texture.render_body(64);
+        assert_eq!(body.dimensions(),(32,64));
+        assert_eq!(*body.get_pixel(0,20),BLUE); // right arm
+        assert_eq!(*body.get_pixel(12,20),GREEN); // body
+        let body = slim.render_body(64);
+        assert_eq!(body.get_pixel(0,20)[3],0); // the slim arm is 3px wide
+
+        let png = texture.to_png().unwrap();
+        assert_eq!(SkinTexture::from_png(&png).unwrap(),texture);
+    }
+
+    #[tokio::test]
+    async fn test_skin_cache(){
+        let server = MockServer::start().await;
+        let png = SkinTexture::new(skin(64,false)).unwrap().to_png().unwrap();
+        Mock::given(method("GET"))
+            .and(path("/texture/0123abcd"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(png))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let base = TestPath(PathBuf::from("test_skin_cache"));
+        let ctx = HttpContext::default();
+        let url = format!("{}/texture/0123abcd",server.uri());
+
+        let texture = SkinTexture::fetch_url(&base,&ctx,&url).await.unwrap();
+        assert!(base.0.join("skins").join("0123abcd.png").exists());
+        let cached = SkinTexture::fetch_url(&base,&ctx,&url).await.unwrap(); // from the disk
+        assert_eq!(texture,cached);
+
+        // a broken file left in the cache is downloaded again.
+        tokio::fs::write(base.0.join("skins").join("0123abcd.png"),b"\x89PNG").await.unwrap();
+        server.verify().await;
+        server.reset().await;
+        Mock::given(method("GET"))
+            .and(path("/texture/0123abcd"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(texture.to_png().unwrap()))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let recovered = SkinTexture::fetch_url(&base,&ctx,&url).await.unwrap();
+        assert_eq!(texture,recovered);
+        assert!(SkinTexture::fetch_url(&base,&ctx,&url).await.is_ok()); // from the disk again
+
+        for url in ["not a url","https://textures.minecraft.net/texture/a%5C..%5C..%5Cx","https://textures.minecraft.net/texture/a:b","https://textures.minecraft.net/"] {
+            assert!(SkinTexture::fetch_url(&base,&ctx,url).await.is_err());
+        }
+        // the query and fragment are not a part of the file name.
+        let cached = SkinTexture::fetch_url(&base,&ctx,&format!("{url}?v=1#skin")).await.unwrap();
+        assert_eq!(texture,cached);
+
+        let missing = format!("{}/texture/missing",server.uri());
+        assert!(SkinTexture::fetch_url(&base,&ctx,&missing).await.is_err());
+        assert!(!base.0.join("skins").join("missing.png").exists());
+
+        tokio::fs::remove_dir_all(base.0).await.unwrap();
+    }
+
+}
@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod test{
+    use chrono::Local;
+    use reginleif::auth::account::{Account, AccountKind, TokenStatus};
+    use reginleif::auth::offline::OfflineAccount;
+    use reginleif_utils::expiring_data::ExpiringData;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use super::super::mock;
+
+    fn account(mc_token:&str) -> ExpiringData<Account>{
+        let mut account = mock::account();
+        account.msa.data.access_token = "msa-revoked".into();
+        account.mc_auth.access_token = mc_token.into();
+        account.into()
+    }
+
+    async fn mount_revoked(server:&MockServer){
+        Mock::given(method("GET"))
+            .and(path("/minecraft/profile"))
+            .and(header("Authorization","Bearer mc-revoked"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_validate(){
+        let server = MockServer::start().await;
+        mock::mount_xbox_minecraft(&server).await;
+        mount_revoked(&server).await;
+        let ctx = mock::context(&server);
+
+        assert!(account("mc-access").data.mc_auth.validate(&ctx).await.unwrap());
+        assert!(!account("mc-revoked").data.mc_auth.validate(&ctx).await.unwrap());
+
+        let mut offline:AccountKind = OfflineAccount::new("Notch").into();
+        assert_eq!(offline.ensure_valid(&ctx,mock::CLIENT_ID).await.unwrap(),TokenStatus::Valid);
+    }
+
+    #[tokio::test]
+    async fn test_revoked_token_is_refreshed(){
+        let server = MockServer::start().await;
+        mock::mount_token(&server).await;
+        mock::mount_xbox_minecraft(&server).await;
+        mount_revoked(&server).await;
+        let ctx = mock::context(&server);
+
+        let mut valid:AccountKind = account("mc-access").into();
+        assert_eq!(valid.ensure_valid(&ctx,mock::CLIENT_ID).await.unwrap(),TokenStatus::Valid);
+
+        let before = Local::now();
+        let mut account:AccountKind = account("mc-revoked").into();
+        assert_eq!(account.ensure_valid(&ctx,mock::CLIENT_ID).await.unwrap(),TokenStatus::Refreshed);
+        assert_eq!(account.access_token(),Some("mc-access"));
+        let AccountKind::Microsoft(account) = account else { unreachable!() };
+        assert!(account.created_at >= before);
+        // msa is refreshed even if it's not expired.
+        assert_eq!(account.data.msa.data.access_token,"msa-access");
+    }
+
+    #[tokio::test]
+    async fn test_revoked_refresh_token(){
+        let server = MockServer::start().await;
+        mock::mount_token_error(&server,"invalid_grant",1).await;
+        mount_revoked(&server).await;
+        let ctx = mock::context(&server);
+
+        let mut account = account("mc-revoked");
+        assert_eq!(account.data.ensure_valid(&ctx,mock::CLIENT_ID).await.unwrap(),TokenStatus::NeedsRelogin);
+        assert_eq!(account.data.mc_auth.access_token,"mc-revoked");
+    }
+
+}
@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod test{
+    use reginleif::auth::microsoft::MicrosoftAuth;
+    use reginleif::auth::xbox::{XboxLiveToken, XboxSecurityError, XboxSecurityToken};
+    use reginleif::auth::xbox::profile::XboxProfile;
+    use serde_json::json;
+    use wiremock::matchers::{body_partial_json, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use super::super::mock;
+
+    async fn xsts_error(body:serde_json::Value) -> XboxSecurityError{
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/xsts/authorize"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(body))
+            .mount(&server)
+            .await;
+
+        XboxSecurityToken::fetch(&mock::context(&server),XboxLiveToken::from("xbl-token"))
+            .await
+            .unwrap_err()
+    }
+
+    #[tokio::test]
+    async fn test_xerr_with_redirect(){
+        let err = xsts_error(json!({
+            "Identity": "0",
+            "XErr": 2148916233u64,
+            "Message": "",
+            "Redirect": "https://start.ui.xboxlive.com/CreateAccount"
+        })).await;
+
+        assert!(matches!(err,XboxSecurityError::NotExist(_)));
+        assert_eq!(err.code(),Some(2148916233));
+        assert_eq!(err.redirect(),Some("https://start.ui.xboxlive.com/CreateAccount"));
+        assert_eq!(err.xerr().unwrap().message,None);
+    }
+
+    #[tokio::test]
+    async fn test_xerr_codes(){
+        let err = xsts_error(json!({"XErr": 2148916227u64})).await;
+        assert!(matches!(err,XboxSecurityError::Banned(_)));
+
+        let err = xsts_error(json!({"XErr": 2148916229u64})).await;
+        assert!(matches!(err,XboxSecurityError::ParentalRestriction(_)));
+
+        let err = xsts_error(json!({"XErr": 2148916234u64})).await;
+        assert!(matches!(err,XboxSecurityError::TermsNotAccepted(_)));
+
+        let err = xsts_error(json!({"XErr": 2148916238u64, "Message": "child"})).await;
+        assert!(matches!(err,XboxSecurityError::AddToFamily(_)));
+        assert_eq!(err.xerr().unwrap().message.as_deref(),Some("child"));
+
+        let err = xsts_error(json!({"XErr": 123u64, "Redirect": "https://example.com"})).await;
+        assert!(matches!(err,XboxSecurityError::Unknown(_)));
+        assert_eq!(err.code(),Some(123));
+        assert!(err.to_string().contains("https://example.com"));
+    }
+
+    #[tokio::test]
+    async fn test_xbox_profile(){
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/user/authenticate"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"Token": "xbl-token"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/xsts/authorize"))
+            .and(body_partial_json(json!({"RelyingParty": "http://xboxlive.com"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "Token": "xsts-xboxlive",
+                "DisplayClaims": {"xui": [{"uhs": "mock-uhs", "xid": "2535400000000000", "gtg": "MockTag"}]}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/users/me/profile/settings"))
+            .and(query_param("settings","Gamertag,GameDisplayPicRaw,UniqueModernGamertag"))
+            .and(header("Authorization","XBL3.0 x=mock-uhs;xsts-xboxlive"))
+            .and(header("x-xbl-contract-version","3"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "profileUsers": [{
+                    "id": "2535400000000000",
+                    "hostId": "2535400000000000",
+                    "settings": [
+                        {"id": "Gamertag", "value": "MockTag"},
+                        {"id": "GameDisplayPicRaw", "value": "https://images-eds-ssl.xboxlive.com/mock"},
+                        {"id": "UniqueModernGamertag", "value": ""}
+                    ],
+                    "isSponsoredUser": false
+                }]
+            })))
+            .mount(&server)
+            .await;
+
+        let msa:MicrosoftAuth = serde_json::from_value(mock::microsoft_auth_json("msa-access","msa-refresh")).unwrap();
+        let profile = XboxProfile::fetch_with_msa(&mock::context(&server),&msa).await.unwrap();
+
+        assert_eq!(profile.xuid,"2535400000000000");
+        assert_eq!(profile.gamertag,"MockTag");
+        assert_eq!(profile.gamerpic.as_deref(),Some("https://images-eds-ssl.xboxlive.com/mock"));
+        assert_eq!(profile.unique_modern_gamertag,None);
+
+        // the token for Minecraft isn't accepted by the mock.
+        let err = XboxSecurityToken::fetch(&mock::context(&server),XboxLiveToken::from("xbl-token")).await;
+        assert!(err.is_err());
+    }
+
+}
@@ -0,0 +1,146 @@
+#[cfg(test)]
+mod test{
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD;
+    use reginleif::auth::account::AccountKind;
+    use reginleif::auth::error::AuthError;
+    use reginleif::auth::yggdrasil::{YggdrasilAccount, YggdrasilError, YggdrasilLogin, YggdrasilServer};
+    use serde_json::json;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use super::super::mock;
+
+    fn metadata_json() -> serde_json::Value{
+        json!({
+            "meta": {"serverName": "Mock Skin", "implementationName": "mock"},
+            "skinDomains": ["example.com"],
+            "signaturePublickey": "-----BEGIN PUBLIC KEY-----\nMOCK\n-----END PUBLIC KEY-----\n"
+        })
+    }
+
+    fn profile(name:&str) -> serde_json::Value{
+        json!({"id": format!("{name}-uuid"), "name": name})
+    }
+
+    async fn mount_server() -> MockServer{
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(200).insert_header("X-Authlib-Injector-API-Location","/api/yggdrasil/"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/yggdrasil/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(metadata_json()))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/yggdrasil/authserver/authenticate"))
+            .and(body_partial_json(json!({"username": "user@example.com", "password": "wrong"})))
+            .respond_with(ResponseTemplate::new(403).set_body_json(json!({
+                "error": "ForbiddenOperationException",
+                "errorMessage": "Invalid credentials. Invalid username or password."
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/yggdrasil/authserver/authenticate"))
+            .and(body_partial_json(json!({"username": "user@example.com", "password": "password"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "accessToken": "ygg-access",
+                "clientToken": "ygg-client",
+                "availableProfiles": [profile("Alice"),profile("Bob")]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/yggdrasil/authserver/refresh"))
+            .and(body_partial_json(json!({"accessToken": "ygg-access", "selectedProfile": profile("Bob")})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "accessToken": "ygg-access-2",
+                "clientToken": "ygg-client",
+                "selectedProfile": profile("Bob")
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/yggdrasil/authserver/validate"))
+            .and(body_partial_json(json!({"accessToken": "ygg-access-2"})))
+            .respond_with(ResponseTemplate::new(403).set_body_json(json!({
+                "error": "ForbiddenOperationException",
+                "errorMessage": "Invalid token."
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/yggdrasil/authserver/refresh"))
+            .and(body_partial_json(json!({"accessToken": "ygg-access-2"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "accessToken": "ygg-access-3",
+                "clientToken": "ygg-client",
+                "selectedProfile": profile("Bob")
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/yggdrasil/authserver/validate"))
+            .and(body_partial_json(json!({"accessToken": "ygg-access-3"})))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/yggdrasil/authserver/invalidate"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn test_yggdrasil_login(){
+        let server = mount_server().await;
+        let ctx = mock::context(&server);
+
+        let ygg = YggdrasilServer::resolve(&ctx,&server.uri()).await.unwrap();
+        assert_eq!(ygg.root,format!("{}/api/yggdrasil",server.uri()));
+
+        let (metadata,_) = ygg.fetch_metadata(&ctx).await.unwrap();
+        assert_eq!(metadata.server_name(),Some("Mock Skin"));
+        assert!(metadata.signature_publickey.unwrap().contains("PUBLIC KEY"));
+
+        let err = YggdrasilAccount::login(&ctx,&ygg,"user@example.com","wrong").await.unwrap_err();
+        assert!(matches!(err,YggdrasilError::ForbiddenOperation(_)));
+        assert!(AuthError::from(err).needs_relogin());
+
+        let select = match YggdrasilAccount::login(&ctx,&ygg,"user@example.com","password").await.unwrap() {
+            YggdrasilLogin::SelectProfile(select) => select,
+            YggdrasilLogin::Ready(_) => panic!("the profile should not be selected"),
+        };
+        assert_eq!(select.session.available_profiles.len(),2);
+        let account = select.select(&ctx,&select.session.available_profiles[1]).await.unwrap();
+        assert_eq!(account.profile.name,"Bob");
+        assert_eq!(account.access_token,"ygg-access-2");
+
+        let args = account.jvm_args("authlib-injector.jar");
+        assert_eq!(args[0],format!("-javaagent:authlib-injector.jar={}",ygg.root));
+        let prefetched = args[1].strip_prefix("-Dauthlibinjector.yggdrasil.prefetched=").unwrap();
+        let prefetched:serde_json::Value = serde_json::from_slice(&STANDARD.decode(prefetched).unwrap()).unwrap();
+        assert_eq!(prefetched,metadata_json());
+
+        let mut account:AccountKind = account.into();
+        account.ensure_fresh(&ctx,mock::CLIENT_ID).await.unwrap();
+        assert_eq!(account.access_token(),Some("ygg-access-3"));
+        assert_eq!(account.uuid(),"Bob-uuid");
+        assert_eq!(account.user_type(),"mojang");
+
+        match account {
+            AccountKind::Yggdrasil(x) => {
+                assert!(x.validate(&ctx).await.unwrap());
+                x.invalidate(&ctx).await.unwrap();
+            }
+            _ => unreachable!(),
+        }
+    }
+
+}
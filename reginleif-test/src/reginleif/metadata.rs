@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod client{
     use std::path::PathBuf;
+    use reginleif::http::HttpContext;
     use reginleif::metadata::client::package::PackageList;
     use reginleif_macro::BaseStorePoint;
 
@@ -18,7 +19,7 @@ mod client{
     #[tokio::test]
     async fn test_metadata_fetch()->anyhow::Result<()>{
 
-        let client = reqwest::Client::new();
+        let ctx = HttpContext::default();
         let base_path = TestPath::new();
         let endpoint = "https://meta.prismlauncher.org/v1/";
 
@@ -27,19 +28,19 @@ mod client{
             ("net.fabricmc.fabric-loader","0.16.0"),
         ];
 
-        let packages = TestPackageList::fetch(&base_path,client.clone(),endpoint).await?;
+        let packages = TestPackageList::fetch(&base_path,&ctx,endpoint).await?;
 
 
         for (uid,version) in test_package{
             let pkg = packages.iter().find(|x| x.uid == uid).unwrap();
-            let pkg_details = pkg.get_details(&base_path,client.clone(),endpoint).await.unwrap();
+            let pkg_details = pkg.get_details(&base_path,&ctx,endpoint).await.unwrap();
             // println!("{:?}",pkg_details);
             let version_info = pkg_details.iter().find(|x| x.version == version).unwrap();
             // println!("{:?}",version_info);
-            let version_details = version_info.get_details(&base_path,client.clone(),endpoint,uid).await?;
+            let version_details = version_info.get_details(&base_path,&ctx,endpoint,uid).await?;
             // println!("{:?}",version_details);
-            if version_details.asset_index.is_some(){
-                let assets = version_details.asset_index.unwrap().fetch_assets_info(&base_path,client.clone()).await?;
+            if let Some(asset_index) = version_details.asset_index{
+                let _assets = asset_index.fetch_assets_info(&base_path,&ctx).await?;
                 // println!("{:?}",assets);
             }
 
@@ -1,2 +1,4 @@
 mod expiring_data;
-mod save_path;
\ No newline at end of file
+mod save_path;
+mod encrypted_store;
+mod secret;
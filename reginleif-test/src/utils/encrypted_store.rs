@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod test{
+    use std::path::PathBuf;
+    use serde::{Deserialize, Serialize};
+    use reginleif_macro::{BaseStorePoint, Storage};
+    use reginleif_utils::encrypted_store::{EncryptedStore, EncryptionError, StoreKey};
+    use reginleif_utils::save_path::Store;
+
+    #[derive(BaseStorePoint,PartialEq,Debug,Clone)]
+    struct TestPath(PathBuf);
+
+    #[derive(Deserialize,Serialize,PartialEq,Debug,Storage)]
+    #[base_on(TestPath)] #[filepath(&["secret.json"])]
+    struct Secret{
+        refresh_token:String,
+    }
+
+    fn secret() -> Secret{
+        Secret{ refresh_token: "very-secret-token".to_string() }
+    }
+
+    #[test]
+    fn test_passphrase(){
+        let path = PathBuf::from("test_encrypted_passphrase");
+        let base = TestPath(path.clone());
+
+        secret().save_encrypted(&base,&StoreKey::passphrase("correct horse")).unwrap();
+        let raw = std::fs::read_to_string(Secret::full_path(&base)).unwrap();
+        assert!(!raw.contains("very-secret-token"));
+        assert!(Secret::is_encrypted(&base));
+        assert!(Secret::load(&base).is_err()); // plaintext load can't read it.
+
+        let loaded = Secret::load_encrypted(&base,&StoreKey::passphrase("correct horse")).unwrap();
+        assert_eq!(loaded,secret());
+
+        let err = Secret::load_encrypted(&base,&StoreKey::passphrase("wrong")).unwrap_err();
+        assert!(matches!(err.downcast_ref::<EncryptionError>(),Some(EncryptionError::Decrypt)));
+
+        let err = Secret::load_encrypted(&base,&StoreKey::Key([0;32])).unwrap_err();
+        assert!(matches!(err.downcast_ref::<EncryptionError>(),Some(EncryptionError::KeyMismatch{..})));
+
+        // a crafted file asks for 4 TiB of memory.
+        let mut envelope:serde_json::Value = serde_json::from_str(&raw).unwrap();
+        envelope["kdf"]["m_cost"] = u32::MAX.into();
+        std::fs::write(Secret::full_path(&base),envelope.to_string()).unwrap();
+        let err = Secret::load_encrypted(&base,&StoreKey::passphrase("correct horse")).unwrap_err();
+        assert!(matches!(err.downcast_ref::<EncryptionError>(),Some(EncryptionError::KdfParams{m_cost:u32::MAX,..})));
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_key_file_and_migration(){
+        let path = PathBuf::from("test_encrypted_key_file");
+        let base = TestPath(path.clone());
+
+        secret().save(&base).unwrap(); // saved by the old version.
+        assert!(!Secret::is_encrypted(&base));
+
+        let key = StoreKey::key_file(&path.join("key")).unwrap();
+        assert!(!format!("{:?}",key).contains('['));
+
+        let loaded = Secret::load_encrypted(&base,&key).unwrap();
+        assert_eq!(loaded,secret());
+        assert!(Secret::is_encrypted(&base)); // migrated.
+        assert!(std::fs::read_dir(&path).unwrap().all(|x| !x.unwrap().file_name().to_string_lossy().ends_with(".tmp")));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(path.join("key")).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777,0o600);
+            let mode = std::fs::metadata(Secret::full_path(&base)).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777,0o600); // the migrated store is owner-only too.
+        }
+
+        let key = StoreKey::key_file(&path.join("key")).unwrap(); // load the same key again.
+        assert_eq!(Secret::load_encrypted(&base,&key).unwrap(),secret());
+
+        std::fs::write(path.join("bad_key"),[0u8;5]).unwrap();
+        let err = StoreKey::key_file(&path.join("bad_key")).unwrap_err();
+        assert!(matches!(err.downcast_ref::<EncryptionError>(),Some(EncryptionError::InvalidKeyFile(5))));
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+}
@@ -48,4 +48,17 @@ mod test{
         test.try_ref(&()).await.unwrap();
     }
 
-}
\ No newline at end of file
+    #[test]
+    pub fn test_expire_in_after_expired(){
+        let test:ExpiringData<_> = TestStruct1{ duration: Duration::from_secs(1) }.into();
+        std::thread::sleep(Duration::from_millis(1100));
+        assert!(test.is_expired());
+        assert_eq!(test.expire_in(),Duration::ZERO); // it used to panic.
+
+        let mut test:ExpiringData<_> = TestStruct1{ duration: Duration::from_secs(60) }.into();
+        test.created_at = chrono::Local::now() + chrono::Duration::seconds(10); // clock changed.
+        assert!(!test.is_expired());
+        assert!(test.expire_in() > Duration::from_secs(60));
+    }
+
+}
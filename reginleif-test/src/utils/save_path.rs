@@ -102,6 +102,7 @@ mod test{
     }
 
 
+    #[allow(dead_code)] // only used to check the derive macro compiles on generic struct.
     #[derive(Serialize,Deserialize,PartialEq,Debug,Save,Load)]
     struct E<T> where T:BaseStorePoint{
         num:String,
@@ -114,6 +115,7 @@ mod test{
         }
     }
 
+    #[allow(dead_code,clippy::upper_case_acronyms)]
     #[derive(Deserialize,Debug)]
     struct OUO{
         uid:String
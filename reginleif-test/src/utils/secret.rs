@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod test{
+    use std::time::Duration;
+    use reginleif::auth::microsoft::MicrosoftAuth;
+    use reginleif::auth::xbox::{XboxLiveToken, XboxSecurityToken};
+    use reginleif_utils::secret::SecretString;
+
+    #[test]
+    fn test_secret_redacted(){
+        let secret = SecretString::from("my-token");
+        assert_eq!(format!("{:?}",secret),"SecretString(***)");
+        assert_eq!(format!("{}",secret),"***");
+        assert_eq!(secret.expose(),"my-token");
+        assert_eq!(secret,"my-token");
+
+        let msa = MicrosoftAuth{
+            token_type: "Bearer".to_string(),
+            scope: "XboxLive.signin offline_access".to_string(),
+            expires_in: Duration::from_secs(3600),
+            access_token: "msa-access".into(),
+            refresh_token: "msa-refresh".into(),
+        };
+        let debug = format!("{:?}",msa);
+        assert!(!debug.contains("msa-access"));
+        assert!(!debug.contains("msa-refresh"));
+
+        assert!(!format!("{:?}",XboxLiveToken::from("xbl-token")).contains("xbl-token"));
+        assert!(!format!("{:?}",XboxSecurityToken::from(("xsts-token","uhs"))).contains("xsts-token"));
+    }
+
+    #[test]
+    fn test_secret_serde(){
+        let secret = SecretString::from("my-token");
+        let json = serde_json::to_string(&secret).unwrap();
+        assert_eq!(json,"\"my-token\"");
+        assert_eq!(serde_json::from_str::<SecretString>(&json).unwrap(),secret);
+
+        // the saved data of the old version can still be loaded.
+        let msa:MicrosoftAuth = serde_json::from_value(serde_json::json!({
+            "token_type": "Bearer",
+            "scope": "XboxLive.signin offline_access",
+            "expires_in": 3600,
+            "access_token": "msa-access",
+            "refresh_token": "msa-refresh"
+        })).unwrap();
+        assert_eq!(msa.refresh_token.expose(),"msa-refresh");
+        assert!(serde_json::to_string(&msa).unwrap().contains("msa-refresh"));
+    }
+
+}
@@ -0,0 +1,347 @@
+//! The module for saving the data which contains secrets (like tokens) encrypted.
+//! It extends [Store](Store) trait with authenticated encryption (XChaCha20-Poly1305),
+//! the key can be derived from a passphrase (Argon2id) or loaded from a key file.
+
+use std::ffi::OsString;
+use std::fmt::{Debug, Formatter};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use argon2::{Argon2, Params};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305, XNonce};
+use chacha20poly1305::aead::Aead;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use zeroize::{Zeroize, Zeroizing};
+use crate::save_path::Store;
+
+/// The version of the encrypted file format.
+const FORMAT_VERSION: u32 = 1;
+
+/// The upper bounds of the Argon2 params read from the file,
+/// so a crafted file can't make the key derivation take forever or use up the memory.
+const MAX_M_COST: u32 = 256 * 1024; // KiB
+const MAX_T_COST: u32 = 16;
+const MAX_P_COST: u32 = 16;
+
+/// The error while encrypting or decrypting the data.
+///
+/// It's returned in [anyhow::Error], you can downcast it.
+#[derive(Error, Debug)]
+pub enum EncryptionError {
+
+    /// The key is wrong, or the file is modified.
+    #[error("Failed to decrypt the data, the key is wrong or the file is corrupted.")]
+    Decrypt,
+
+    /// The file is encrypted with passphrase but a key file is given, or vice versa.
+    #[error("The file is encrypted with {expected}, but the other kind of key is given.")]
+    KeyMismatch {
+        /// The kind of key the file needs.
+        expected: &'static str,
+    },
+
+    /// The file is written by a newer version.
+    #[error("Unsupported encrypted file version: {0}")]
+    UnsupportedVersion(u32),
+
+    /// The Argon2 params of the file exceed the upper bounds.
+    #[error("The key derivation params (m_cost:{m_cost}, t_cost:{t_cost}, p_cost:{p_cost}) exceed the limits.")]
+    KdfParams {
+        m_cost: u32,
+        t_cost: u32,
+        p_cost: u32,
+    },
+
+    /// The key file is not 32 bytes.
+    #[error("The key file should be 32 bytes, but it's {0} bytes.")]
+    InvalidKeyFile(usize),
+
+}
+
+/// The key to encrypt and decrypt the data.
+///
+/// The [Debug] output of this struct never contains the secret, and the secret is zeroized on drop.
+#[derive(Clone)]
+pub enum StoreKey {
+    /// The key is derived from the passphrase with a random salt on every save.
+    Passphrase(String),
+    /// The raw 32 bytes key, usually loaded from a key file by [StoreKey::key_file].
+    Key([u8; 32]),
+}
+
+impl Debug for StoreKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StoreKey::Passphrase(_) => f.write_str("StoreKey::Passphrase(***)"),
+            StoreKey::Key(_) => f.write_str("StoreKey::Key(***)"),
+        }
+    }
+}
+
+impl Drop for StoreKey {
+    fn drop(&mut self) {
+        match self {
+            StoreKey::Passphrase(passphrase) => passphrase.zeroize(),
+            StoreKey::Key(key) => key.zeroize(),
+        }
+    }
+}
+
+impl StoreKey {
+
+    /// Create the key from the passphrase.
+    pub fn passphrase(passphrase: &str) -> Self {
+        Self::Passphrase(passphrase.to_string())
+    }
+
+    /// Load the key from the key file, a new random key will be created if the file doesn't exist.
+    ///
+    /// On unix, the new key file is created only readable by the owner, and it's never overwritten.
+    /// The new key file is removed if it can't be written completely.
+    ///
+    /// # Arguments
+    /// * `path`: the path of the key file.
+    pub fn key_file(path: &Path) -> anyhow::Result<Self> {
+        if path.exists() {
+            let bytes = Zeroizing::new(std::fs::read(path)?);
+            let key: [u8; 32] = bytes.as_slice()
+                .try_into()
+                .map_err(|_| EncryptionError::InvalidKeyFile(bytes.len()))?;
+            return Ok(Self::Key(key));
+        }
+
+        let key: [u8; 32] = rand::random();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(path)?;
+        // don't leave a truncated key file, it would be loaded as a broken key next time.
+        file.write_all(&key)
+            .and_then(|_| file.sync_all())
+            .inspect_err(|_| {
+                let _ = std::fs::remove_file(path);
+            })?;
+        Ok(Self::Key(key))
+    }
+
+}
+
+/// How the key of the file is derived.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "algorithm", rename_all = "lowercase")]
+enum Kdf {
+    /// Derived from the passphrase.
+    Argon2id {
+        salt: String,
+        m_cost: u32,
+        t_cost: u32,
+        p_cost: u32,
+    },
+    /// The raw key is used directly.
+    None,
+}
+
+impl Kdf {
+    fn name(&self) -> &'static str {
+        match self {
+            Kdf::Argon2id { .. } => "passphrase",
+            Kdf::None => "key file",
+        }
+    }
+}
+
+/// The content of the encrypted file.
+#[derive(Serialize, Deserialize, Debug)]
+struct Envelope {
+    /// The version of the format, it's also the mark of the encrypted file.
+    reginleif_encrypted: u32,
+    kdf: Kdf,
+    nonce: String,
+    ciphertext: String,
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], params: Params) -> anyhow::Result<Zeroizing<[u8; 32]>> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, key.as_mut())
+        .map_err(|e| anyhow::anyhow!("Failed to derive key: {}", e))?;
+    Ok(key)
+}
+
+fn encrypt(plaintext: &[u8], key: &StoreKey) -> anyhow::Result<Envelope> {
+    let (raw_key, kdf) = match key {
+        StoreKey::Passphrase(passphrase) => {
+            let salt: [u8; 16] = rand::random();
+            let params = Params::default();
+            let kdf = Kdf::Argon2id {
+                salt: STANDARD.encode(salt),
+                m_cost: params.m_cost(),
+                t_cost: params.t_cost(),
+                p_cost: params.p_cost(),
+            };
+            (derive_key(passphrase, &salt, params)?, kdf)
+        }
+        StoreKey::Key(key) => (Zeroizing::new(*key), Kdf::None),
+    };
+
+    let nonce: [u8; 24] = rand::random();
+    let ciphertext = XChaCha20Poly1305::new(Key::from_slice(raw_key.as_ref()))
+        .encrypt(XNonce::from_slice(&nonce), plaintext)
+        .map_err(|_| anyhow::anyhow!("Failed to encrypt the data"))?;
+
+    Ok(Envelope {
+        reginleif_encrypted: FORMAT_VERSION,
+        kdf,
+        nonce: STANDARD.encode(nonce),
+        ciphertext: STANDARD.encode(ciphertext),
+    })
+}
+
+fn decrypt(envelope: &Envelope, key: &StoreKey) -> anyhow::Result<Zeroizing<Vec<u8>>> {
+    if envelope.reginleif_encrypted > FORMAT_VERSION {
+        return Err(EncryptionError::UnsupportedVersion(envelope.reginleif_encrypted).into());
+    }
+
+    let raw_key = match (&envelope.kdf, key) {
+        (Kdf::Argon2id { salt, m_cost, t_cost, p_cost }, StoreKey::Passphrase(passphrase)) => {
+            if *m_cost > MAX_M_COST || *t_cost > MAX_T_COST || *p_cost > MAX_P_COST {
+                return Err(EncryptionError::KdfParams { m_cost: *m_cost, t_cost: *t_cost, p_cost: *p_cost }.into());
+            }
+            let params = Params::new(*m_cost, *t_cost, *p_cost, Some(32))
+                .map_err(|e| anyhow::anyhow!("Invalid argon2 params: {}", e))?;
+            derive_key(passphrase, &STANDARD.decode(salt)?, params)?
+        }
+        (Kdf::None, StoreKey::Key(key)) => Zeroizing::new(*key),
+        (kdf, _) => return Err(EncryptionError::KeyMismatch { expected: kdf.name() }.into()),
+    };
+
+    let nonce = STANDARD.decode(&envelope.nonce)?;
+    if nonce.len() != 24 {
+        return Err(EncryptionError::Decrypt.into());
+    }
+
+    XChaCha20Poly1305::new(Key::from_slice(raw_key.as_ref()))
+        .decrypt(XNonce::from_slice(&nonce), STANDARD.decode(&envelope.ciphertext)?.as_slice())
+        .map(Zeroizing::new)
+        .map_err(|_| EncryptionError::Decrypt.into())
+}
+
+/// Write the file to a temporary file and rename it, so the old file is kept if the writing fails.
+///
+/// The temporary file is created owner-only like the key file, and the rename keeps it that way.
+fn write_replace(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut tmp = OsString::from(path.as_os_str());
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+
+    // a stale temporary file may have looser permissions, the mode only applies to a new file.
+    match std::fs::remove_file(&tmp) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    options.open(&tmp)
+        .and_then(|mut file| file.write_all(contents).and_then(|_| file.sync_all()))
+        .and_then(|_| std::fs::rename(&tmp, path))
+        .inspect_err(|_| {
+            let _ = std::fs::remove_file(&tmp);
+        })
+}
+
+/// A trait to save and load the [Store](Store) data encrypted.
+///
+/// This trait is implemented for all [Store](Store) types, and it uses the same path as [Store](Store).
+/// [load_encrypted](EncryptedStore::load_encrypted) also reads the plaintext file saved by [Store::save],
+/// and rewrites it encrypted, so you can switch to this trait without losing the saved data.
+///
+/// # Example
+/// ```no_run
+/// use std::path::PathBuf;
+/// use serde::{Deserialize, Serialize};
+/// use reginleif_macro::{BaseStorePoint, Storage};
+/// use reginleif_utils::encrypted_store::{EncryptedStore, StoreKey};
+///
+/// #[derive(BaseStorePoint,PartialEq,Debug)]
+/// struct TestPath(PathBuf);
+///
+/// #[derive(Deserialize,Serialize,PartialEq,Debug,Storage)]
+/// #[base_on(TestPath)] #[filepath(&["secret.json"])]
+/// struct Secret(String);
+///
+/// let base = TestPath(PathBuf::from("data"));
+/// let key = StoreKey::key_file(&PathBuf::from("data/key")).unwrap();
+///
+/// Secret("token".to_string()).save_encrypted(&base,&key).unwrap();
+/// let secret = Secret::load_encrypted(&base,&key).unwrap();
+/// ```
+pub trait EncryptedStore: Store {
+
+    /// Save the data encrypted.
+    ///
+    /// # Arguments
+    /// * `base`: the base path of the data.
+    /// * `key`: the key to encrypt the data.
+    fn save_encrypted(&self, base: &Self::AcceptStorePoint, key: &StoreKey) -> anyhow::Result<()> {
+        let base_path = Self::full_path(base);
+        let plaintext = Zeroizing::new(serde_json::to_string(self)?);
+        let envelope = encrypt(plaintext.as_bytes(), key)?;
+
+        std::fs::create_dir_all(base_path.parent().ok_or(anyhow::anyhow!("No parent"))?)?;
+        write_replace(&base_path, serde_json::to_string(&envelope)?.as_bytes())?;
+
+        Ok(())
+    }
+
+    /// Load the encrypted data.
+    ///
+    /// If the file is saved in plaintext, it will be rewritten encrypted with the key,
+    /// the plaintext file is replaced only after the encrypted one is written completely.
+    ///
+    /// # Arguments
+    /// * `base`: the base path of the data.
+    /// * `key`: the key to decrypt the data.
+    fn load_encrypted(base: &Self::AcceptStorePoint, key: &StoreKey) -> anyhow::Result<Self> {
+        let base_path = Self::full_path(base);
+        let json = Zeroizing::new(std::fs::read_to_string(base_path)?);
+
+        match serde_json::from_str::<Envelope>(&json) {
+            Ok(envelope) => Ok(serde_json::from_slice(&decrypt(&envelope, key)?)?),
+            Err(_) => { // plaintext, migrate it.
+                let data: Self = serde_json::from_str(&json)?;
+                data.save_encrypted(base, key)?;
+                Ok(data)
+            }
+        }
+    }
+
+    /// Check the saved file is encrypted or not, return `false` if the file doesn't exist.
+    ///
+    /// # Arguments
+    /// * `base`: the base path of the data.
+    fn is_encrypted(base: &Self::AcceptStorePoint) -> bool {
+        std::fs::read_to_string(Self::full_path(base))
+            .map(|json| serde_json::from_str::<Envelope>(&json).is_ok())
+            .unwrap_or(false)
+    }
+
+}
+
+impl<T> EncryptedStore for T where T: Store {}
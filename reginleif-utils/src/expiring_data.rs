@@ -1,5 +1,5 @@
 use std::time::Duration;
-use chrono::Local;
+use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
 use crate::serde_convert::{local_to_string, string_to_local};
 use anyhow::Result;
@@ -20,7 +20,7 @@ pub struct ExpiringData<T> where T:Expirable+Refreshable
     pub data: T,
     /// the time this object created/refresh
     #[serde(deserialize_with = "string_to_local", serialize_with = "local_to_string")]
-    pub created_at: chrono::DateTime<Local>,
+    pub created_at: DateTime<Local>,
 
 }
 
@@ -48,6 +48,14 @@ pub trait Expirable{
     /// Use in the [is_expired](ExpiringData::is_expired) function.
     fn get_duration(&self) -> Duration;
 
+    /// Get the time the data expires, if the data itself knows it (like a JWT with `exp` claim).
+    ///
+    /// When it returns `Some`, it's used instead of `created_at + get_duration()`,
+    /// so the data loaded from disk still knows its true remaining lifetime.
+    fn expires_at(&self) -> Option<DateTime<Local>>{
+        None
+    }
+
 }
 
 /// A trait for data that can be refreshed.
@@ -107,13 +115,23 @@ where
     T: Expirable + Refreshable,
 {
 
+    /// Get the time the data expires.
+    ///
+    /// Return `None` if the time is too far to represent, which means the data never expires.
+    pub fn deadline(&self) -> Option<DateTime<Local>>{
+        if let Some(expires_at) = self.data.expires_at() {
+            return Some(expires_at);
+        }
+        let duration = chrono::Duration::from_std(self.data.get_duration()).ok()?;
+        self.created_at.checked_add_signed(duration)
+    }
+
     /// Check the data is expired or not.
     pub fn is_expired(&self) -> bool{
-        let duration = (Local::now() - self.created_at)
-            .to_std()
-            .expect("Failed to convert chrono::Duration to std::Duration");
-
-        duration >= self.data.get_duration()
+        match self.deadline() {
+            Some(deadline) => Local::now() >= deadline,
+            None => false,
+        }
     }
     
     /// Get the reference of data.
@@ -138,12 +156,12 @@ where
         Ok(&self.data)
     }
 
+    /// Get the time before the data expires, return zero if the data is expired.
     pub fn expire_in(&self) -> Duration{
-        let duration = (Local::now() - self.created_at)
-            .to_std()
-            .expect("Failed to convert chrono::Duration to std::Duration");
-
-        self.data.get_duration() - duration
+        match self.deadline() {
+            Some(deadline) => (deadline - Local::now()).to_std().unwrap_or_default(),
+            None => Duration::MAX,
+        }
     }
 
 }
@@ -1,4 +1,6 @@
 pub mod expiring_data;
 pub mod serde_convert;
 pub mod save_path;
+pub mod encrypted_store;
 pub mod sha;
+pub mod secret;
@@ -4,11 +4,10 @@
 
 use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
-use reqwest::{Client, Error, Response};
+use reqwest::Client;
 use serde::{Serialize};
 use serde::de::DeserializeOwned;
 use sha1::Digest as Digest1;
-use log::log;
 use crate::sha::SHA;
 
 /// A trait for the base path of the data.
@@ -146,7 +145,7 @@ pub trait Store:Serialize+DeserializeOwned{
     /// # Arguments
     /// * `base`: the base path of the data.
     fn save(&self, base: &Self::AcceptStorePoint) -> anyhow::Result<()> {
-        let base_path = Self::full_path(&base);
+        let base_path = Self::full_path(base);
 
         std::fs::create_dir_all(base_path.parent().ok_or(anyhow::anyhow!("No parent"))?)?;
         std::fs::write(base_path,serde_json::to_string(self)?.as_bytes())?;
@@ -161,7 +160,7 @@ pub trait Store:Serialize+DeserializeOwned{
     /// * `base`: the base path of the data.
     fn load(base: &Self::AcceptStorePoint) -> anyhow::Result<Self> {
 
-        let base_path = Self::full_path(&base);
+        let base_path = Self::full_path(base);
 
         let json = std::fs::read_to_string(base_path)?;
         Ok(serde_json::from_str(&json)?)
@@ -248,7 +247,7 @@ pub trait Save:ExpandStorePoint+Serialize{
     /// # Arguments
     /// * `base`: the base path of the data.
     fn save(&self, base:&Self::AcceptStorePoint) -> anyhow::Result<()>{
-        let base_path = base.get_base().join(&self.get_suffix());
+        let base_path = base.get_base().join(self.get_suffix());
 
         std::fs::create_dir_all(base_path.parent().ok_or(anyhow::anyhow!("No parent"))?)?;
         std::fs::write(base_path,serde_json::to_string(self)?.as_bytes())?;
@@ -326,7 +325,6 @@ pub trait Save:ExpandStorePoint+Serialize{
 /// }
 ///
 /// ```
-
 pub trait Load:DeserializeOwned{
 
     /// The type of the base path you have to accept.
@@ -363,11 +361,43 @@ async fn handle_file_not_exist(path:&PathBuf, client: &Client, url:&str) -> anyh
 async fn try_download(client: &Client, url:&str, path:&PathBuf) -> anyhow::Result<()>{
     match client.get(url).send().await?.bytes().await{
         Ok(data) => {tokio::fs::write(&path, data).await?;}
-        Err(e) => {log::error!("Error while fetching {url}, details:{}",e.to_string())} // we won't do anything if the data is not fetched successfully.
+        Err(e) => {log::error!("Error while fetching {url}, details:{}",e)} // we won't do anything if the data is not fetched successfully.
     };
     Ok(())
 }
 
+/// Like [Cache::try_cache], but for the raw file like images, the content is returned as bytes.
+///
+/// If the file exists, return the content from disk, otherwise fetch it from the url and save it to the disk.
+/// The content won't be saved if the request failed (including the error status code), and it's
+/// written to a temp file first then renamed, so an interrupted write never leaves a partial file.
+///
+/// # Arguments
+/// * `base`: the base path of the data.
+/// * `suffix`: the relative path of the data from base.
+/// * `client`: the reqwest client.
+/// * `url`: the url to fetch the data.
+pub async fn try_cache_bytes<P: AsRef<Path>>(base:&impl BaseStorePoint, suffix:P, client: &Client, url:&str) -> anyhow::Result<Vec<u8>>{
+    let path = base.get_base().join(suffix);
+
+    if let Ok(data) = tokio::fs::read(&path).await {
+        return Ok(data);
+    }
+
+    let data = client.get(url).send().await?.error_for_status()?.bytes().await?;
+    tokio::fs::create_dir_all(path.parent().ok_or(anyhow::anyhow!("No parent"))?).await?;
+
+    let mut tmp = path.clone().into_os_string();
+    tmp.push(".tmp");
+    tokio::fs::write(&tmp, &data).await?;
+    if let Err(e) = tokio::fs::rename(&tmp, &path).await {
+        let _ = tokio::fs::remove_file(&tmp).await;
+        return Err(e.into());
+    }
+
+    Ok(data.to_vec())
+}
+
 pub trait Cache:DeserializeOwned{
 
     type AcceptStorePoint:BaseStorePoint;
@@ -377,7 +407,7 @@ pub trait Cache:DeserializeOwned{
         -> impl std::future::Future<Output = anyhow::Result<Self>> + Send{async move {
         
         let path = base.get_base().join(&suffix);
-        try_download(&client,&url,&path).await?;
+        try_download(&client,url,&path).await?;
 
         let content = std::fs::read_to_string(path)?;
         let json = serde_json::from_str(&content)?;
@@ -428,7 +458,7 @@ pub trait Cache:DeserializeOwned{
         };
 
         if !valid{
-            try_download(&client,&url,&path).await?;
+            try_download(&client,url,&path).await?;
         }
 
         let content = std::fs::read_to_string(path)?; // we won't check the sha again, because we already download it.
@@ -463,6 +493,7 @@ pub struct CacheBuilder<T:BaseStorePoint,U:Cache>{
 impl <T,U> CacheBuilder<T, U> where U:Cache<AcceptStorePoint=T>, T:BaseStorePoint+Clone{
 
     /// append the path to the buffer.
+    #[allow(clippy::should_implement_trait)]
     pub fn add<P: AsRef<Path>+Send>(mut self,args:P) -> Self{
         self.buf.push(args);
         self
@@ -0,0 +1,100 @@
+//! The module for the string which holds a secret, like access token or refresh token.
+
+use std::fmt::{Debug, Display, Formatter};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::Zeroize;
+
+/// A string which holds a secret, like access token or refresh token.
+///
+/// The secret is redacted in [Debug] and [Display], so it won't be leaked to logs or crash reports
+/// by accident, and the memory is zeroized on drop.
+/// It's serialized as a plain string, so the saved data is compatible with [String].
+///
+/// Use [expose](SecretString::expose) when you really need the secret, like sending it in a request.
+/// **DO NOT** pass it to the function which accepts `impl Display`, like `format!` or `bearer_auth`,
+/// you will get the redacted text.
+///
+/// # Example
+/// ```
+/// use reginleif_utils::secret::SecretString;
+///
+/// let token = SecretString::from("my-token");
+/// assert_eq!(format!("{:?}",token),"SecretString(***)");
+/// assert_eq!(token.to_string(),"***");
+/// assert_eq!(token.expose(),"my-token");
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SecretString(String);
+
+impl SecretString {
+
+    /// Create a new secret.
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self(secret.into())
+    }
+
+    /// Get the secret.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    /// Check the secret is empty or not.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Debug for SecretString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretString(***)")
+    }
+}
+
+impl Display for SecretString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("***")
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(secret: String) -> Self {
+        Self(secret)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(secret: &str) -> Self {
+        Self(secret.to_string())
+    }
+}
+
+impl PartialEq<str> for SecretString {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for SecretString {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl Serialize for SecretString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self(String::deserialize(deserializer)?))
+    }
+}
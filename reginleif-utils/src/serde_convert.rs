@@ -5,12 +5,12 @@ use serde_json::Value;
 
 /// This function is intend to be used with serde's `deserialize_with` attribute.
 /// Convert a string to a DateTime<Local>.
-/// The string should be in RFC3339 format.
+/// The string should be in RFC3339 format, otherwise an error is returned.
 pub fn string_to_local<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Local>, D::Error> {
     Ok(match Value::deserialize(deserializer)? {
         Value::String(str) => {
             let t = str.as_str();
-            let datetime = DateTime::parse_from_rfc3339(t).expect("Failed to parse time");
+            let datetime = DateTime::parse_from_rfc3339(t).map_err(de::Error::custom)?;
             datetime.with_timezone(&Local)
         }
         _ => return Err(de::Error::custom("wrong type")),
@@ -1,9 +1,25 @@
-/// All the thing that related to Microsoft OAuth2.0 service. (using devicecode flow)
+/// All the thing that related to Microsoft OAuth2.0 service. (using devicecode or authorization code flow)
 pub mod microsoft;
 /// All the thing that related to Xbox service.
 pub mod xbox;
 /// All the thing that related to Minecraft service.
 pub mod minecraft;
+/// All the thing that related to Yggdrasil-compatible auth server. (authlib-injector)
+pub mod yggdrasil;
 /// A wrapper for Account data.
 pub mod account;
+/// The offline account which doesn't need to login, for LAN play and development.
+pub mod offline;
+/// The manager which holds multiple accounts.
+pub mod manager;
+/// Import the accounts from other launchers, like Prism Launcher and the official launcher.
+pub mod import;
+/// The scheduler which refreshes the accounts before they expire.
+pub mod scheduler;
+/// The endpoints that the auth chain will request.
+pub mod endpoint;
+/// The errors that can occur in the auth chain.
+pub mod error;
+/// The rate limiter and retry of the requests to auth services.
+pub mod rate_limit;
 mod constant;
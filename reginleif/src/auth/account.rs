@@ -1,17 +1,23 @@
 use std::time::Duration;
 use async_trait::async_trait;
-use reqwest::Client;
+use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
+use crate::auth::error::AuthError;
 use crate::auth::microsoft::MicrosoftAuth;
 use crate::auth::minecraft::{MinecraftAuth, Profile};
+use crate::auth::minecraft::entitlement::{Entitlements, GameOwnership};
+use crate::auth::minecraft::name::NameAvailability;
+use crate::auth::offline::OfflineAccount;
+use crate::auth::yggdrasil::YggdrasilAccount;
 use crate::auth::xbox::{XboxLiveToken, XboxSecurityToken};
 use reginleif_utils::expiring_data::{Expirable, ExpiringData, Refreshable};
+use crate::http::HttpContext;
 
 
 /// Account
 /// 
 /// This struct is used to store the user's account information.
-/// including Minecraft Auth, Profile, and Microsoft Auth.
+/// including Minecraft Auth, Profile, Microsoft Auth and the game ownership.
 /// 
 /// This struct also impl [Expirable](crate::utils::expiring_data::Expirable) and [Refreshable](crate::utils::expiring_data::Refreshable) trait,
 /// this means you can use it with ExpiringData<T> and you can refresh the account if the data is expired.
@@ -20,7 +26,7 @@ use reginleif_utils::expiring_data::{Expirable, ExpiringData, Refreshable};
 /// To use this struct, you need to create a new account with the data you get from the auth process.
 /// # Example
 /// ```no_run
-/// use reqwest::Client;
+/// use reginleif::http::HttpContext;
 /// use reginleif::auth::account::Account;
 /// use reginleif::auth::microsoft::{DeviceCode, MicrosoftAuthError};
 /// use reginleif::auth::minecraft::{MinecraftAuth, Profile};
@@ -30,9 +36,9 @@ use reginleif_utils::expiring_data::{Expirable, ExpiringData, Refreshable};
 ///
 ///
 /// async fn example(){
-///        let client = reqwest::Client::new();
+///        let ctx = HttpContext::default();
 ///        let client_id = "47f3e635-2886-4628-a1c2-fd8a9f4d7a5f";
-///        let res = DeviceCode::fetch(&client,client_id).await;
+///        let res = DeviceCode::fetch(&ctx,client_id).await;
 ///
 ///        let device_code = match res{
 ///            Ok(device_code) => {
@@ -47,7 +53,7 @@ use reginleif_utils::expiring_data::{Expirable, ExpiringData, Refreshable};
 ///
 ///
 ///        let mut res = loop{
-///            let result = device_code.exchange(&client,client_id).await;
+///            let result = device_code.exchange(&ctx,client_id).await;
 ///            let res = match result{
 ///                Ok(res) => {res}
 ///                Err(e) => {
@@ -67,14 +73,14 @@ use reginleif_utils::expiring_data::{Expirable, ExpiringData, Refreshable};
 ///
 ///        let cloned = res.clone();
 ///
-///        let xbox_live_token = XboxLiveToken::fetch(&client,&res.data.access_token).await.unwrap();
+///        let xbox_live_token = XboxLiveToken::fetch(&ctx,res.data.access_token.expose()).await.unwrap();
 ///        println!("{:?}",xbox_live_token);
-///        let xbox_security_token = XboxSecurityToken::fetch(&client,xbox_live_token).await.unwrap();
+///        let xbox_security_token = XboxSecurityToken::fetch(&ctx,xbox_live_token).await.unwrap();
 ///        println!("{:?}",xbox_security_token);
 ///        
-///        let minecraft_auth = MinecraftAuth::fetch(&client,xbox_security_token).await.unwrap();
+///        let minecraft_auth = MinecraftAuth::fetch(&ctx,xbox_security_token).await.unwrap();
 ///        println!("{:?}",minecraft_auth);
-///        let profile = Profile::fetch(&client,&minecraft_auth).await.unwrap();
+///        let profile = Profile::fetch(&ctx,&minecraft_auth).await.unwrap();
 ///        println!("{:?}",profile);
 ///
 ///        let account:Account = (minecraft_auth,profile,res.clone()).into(); // convert into Account
@@ -84,13 +90,194 @@ use reginleif_utils::expiring_data::{Expirable, ExpiringData, Refreshable};
 pub struct Account{
     pub mc_auth:MinecraftAuth,
     pub profile:Profile,
-    pub msa:ExpiringData<MicrosoftAuth>
+    pub msa:ExpiringData<MicrosoftAuth>,
+    /// How the account owns the game, it's updated on every refresh.
+    #[serde(default)]
+    pub ownership:GameOwnership,
 }
 
 impl Expirable for Account{
     fn get_duration(&self) -> Duration {
         self.mc_auth.expires_in
     }
+
+    fn expires_at(&self) -> Option<DateTime<Local>> {
+        self.mc_auth.expires_at()
+    }
+}
+
+impl Account{
+
+    /// Re-fetch minecraft auth, ownership and profile, and refresh Microsoft Auth if it's expired.
+    ///
+    /// This is the typed version of [Refreshable::refresh], you can use the returned [AuthError]
+    /// to decide whether to retry or to ask the user to login again.
+    ///
+    /// Note this function won't update `created_at` of [ExpiringData].
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    /// * `client_id`: The client id of your app.
+    pub async fn renew(&mut self, ctx:&HttpContext, client_id:&str) -> Result<(),AuthError>{
+        self.renew_with(ctx,client_id,false).await
+    }
+
+    /// Check the token with Minecraft services, and refresh the account if the token is revoked.
+    ///
+    /// Unlike [renew](Account::renew), Microsoft Auth is always refreshed when the token is revoked,
+    /// because it's usually revoked at the same time even if it's not expired yet.
+    ///
+    /// Note this function won't update `created_at` of [ExpiringData], use [AccountKind::ensure_valid] if you don't want
+    /// to handle it by yourself.
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    /// * `client_id`: The client id of your app.
+    ///
+    /// # Returns
+    /// * [TokenStatus::NeedsRelogin] if the refresh token is revoked too, other errors of the refresh are returned as is.
+    pub async fn ensure_valid(&mut self, ctx:&HttpContext, client_id:&str) -> Result<TokenStatus,AuthError>{
+        if self.mc_auth.validate(ctx).await? {
+            return Ok(TokenStatus::Valid);
+        }
+        TokenStatus::from_refresh(self.renew_with(ctx,client_id,true).await)
+    }
+
+    async fn renew_with(&mut self, ctx:&HttpContext, client_id:&str, force_msa:bool) -> Result<(),AuthError>{
+
+        // msa is refreshed in place if expired, so the rotated refresh token is kept in the account.
+        if force_msa || self.msa.is_expired(){
+            self.msa.data.renew(ctx,client_id).await?;
+            self.msa.created_at = Local::now();
+        }
+
+        let (mc_auth,ownership) = sign_in(ctx,&self.msa.data).await?;
+        let profile = Profile::fetch(ctx,&mc_auth).await?;
+
+        self.mc_auth = mc_auth;
+        self.profile = profile;
+        self.ownership = ownership;
+
+        Ok(())
+    }
+
+    /// Build the account from Microsoft Auth, which is the last step of the login.
+    ///
+    /// If the account owns the game but doesn't have a profile yet (the first login after buying the game),
+    /// [AccountSetup::NeedsProfile] will be returned, and you can ask the user to choose a name.
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    /// * `msa`: The Microsoft Auth you get from device code or authorization code flow.
+    pub async fn from_msa(ctx:&HttpContext, msa:ExpiringData<MicrosoftAuth>) -> Result<AccountSetup,AuthError>{
+        let (mc_auth,ownership) = sign_in(ctx,&msa.data).await?;
+
+        match Profile::fetch(ctx,&mc_auth).await {
+            Ok(profile) => Ok(AccountSetup::Ready(Self{ mc_auth, profile, msa, ownership })),
+            Err(AuthError::ProfileNotFound) => Ok(AccountSetup::NeedsProfile(ProfileSetup{ mc_auth, msa, ownership })),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Change the name of the profile, and update the profile of the account.
+    ///
+    /// You can check [Profile::name_change_info] and [Profile::check_name] before calling this.
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    /// * `name`: The new name.
+    pub async fn change_name(&mut self, ctx:&HttpContext, name:&str) -> Result<(),AuthError>{
+        self.profile = Profile::change_name(ctx,&self.mc_auth,name).await?;
+        Ok(())
+    }
+
+}
+
+/// Xbox Live -> XSTS -> Minecraft Auth -> entitlements.
+///
+/// The ownership is only informational, so the sign in won't fail if the entitlements can't be fetched.
+async fn sign_in(ctx:&HttpContext, msa:&MicrosoftAuth) -> Result<(MinecraftAuth,GameOwnership),AuthError>{
+    let xbox_live = XboxLiveToken::fetch(ctx,msa.access_token.expose()).await?;
+    let xbox_security = XboxSecurityToken::fetch(ctx,xbox_live).await?;
+    let mc_auth = MinecraftAuth::fetch(ctx,xbox_security).await?;
+    let ownership = match Entitlements::fetch_license(ctx,&mc_auth).await {
+        Ok(entitlements) => entitlements.ownership(),
+        Err(e) => {
+            log::warn!("Error while fetching entitlements, the ownership is unknown. details:{}",e);
+            GameOwnership::Unknown
+        }
+    };
+    Ok((mc_auth,ownership))
+}
+
+/// The result of [Account::ensure_valid] and [AccountKind::ensure_valid].
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum TokenStatus{
+    /// The token is accepted by the server, nothing is changed.
+    Valid,
+    /// The token is expired or revoked, and the account is refreshed, you should save it.
+    Refreshed,
+    /// The token can't be refreshed anymore, ask the user to login again.
+    NeedsRelogin,
+}
+
+impl TokenStatus{
+
+    fn from_refresh(result:Result<(),AuthError>) -> Result<Self,AuthError>{
+        match result {
+            Ok(()) => Ok(TokenStatus::Refreshed),
+            Err(e) if e.needs_relogin() => Ok(TokenStatus::NeedsRelogin),
+            Err(e) => Err(e),
+        }
+    }
+
+}
+
+/// The result of [Account::from_msa].
+#[derive(Debug,Clone)]
+pub enum AccountSetup{
+    /// The account is ready to use.
+    Ready(Account),
+    /// The account doesn't have a profile yet, use [ProfileSetup::create] to create it.
+    NeedsProfile(ProfileSetup),
+}
+
+/// The account which is signed in but doesn't have a profile yet.
+///
+/// Check [ownership](ProfileSetup::ownership) first, the profile can only be created if the account owns the game.
+#[derive(Debug,Clone)]
+pub struct ProfileSetup{
+    pub mc_auth:MinecraftAuth,
+    pub msa:ExpiringData<MicrosoftAuth>,
+    pub ownership:GameOwnership,
+}
+
+impl ProfileSetup{
+
+    /// Check whether the name can be used.
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    /// * `name`: The name to check.
+    pub async fn check_name(&self, ctx:&HttpContext, name:&str) -> Result<NameAvailability,AuthError>{
+        Profile::check_name(ctx,&self.mc_auth,name).await
+    }
+
+    /// Create the profile with the name, and build the account.
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    /// * `name`: The name of the new profile.
+    pub async fn create(&self, ctx:&HttpContext, name:&str) -> Result<Account,AuthError>{
+        let profile = Profile::create(ctx,&self.mc_auth,name).await?;
+        Ok(Account{
+            mc_auth:self.mc_auth.clone(),
+            profile,
+            msa:self.msa.clone(),
+            ownership:self.ownership,
+        })
+    }
+
 }
 
 /// Refreshable
@@ -101,24 +288,14 @@ impl Expirable for Account{
 #[async_trait]
 impl Refreshable for Account{
 
-    /// client id
-    type Args = String;
+    /// HTTP context and client id
+    type Args = (HttpContext,String);
 
     /// re-fetch minecraft auth and profile.
-    async fn refresh(&mut self, client_id:&String) -> anyhow::Result<()> {
-
-        let client = Client::new();
-
-        let msa = &self.msa.try_ref(&(client.clone(),client_id.to_string())).await?;
-        let xbox_live = XboxLiveToken::fetch(&client,&msa.access_token).await?;
-        let xbox_security = XboxSecurityToken::fetch(&client,xbox_live).await?;
-        let mc_auth = MinecraftAuth::fetch(&client,xbox_security).await?;
-        let profile = Profile::fetch(&client,&mc_auth).await?;
-
-        self.mc_auth = mc_auth;
-        self.profile = profile;
-
-        Ok(())
+    ///
+    /// The error can be downcast to [AuthError].
+    async fn refresh(&mut self, (ctx,client_id):&(HttpContext,String)) -> anyhow::Result<()> {
+        Ok(self.renew(ctx,client_id).await?)
     }
 }
 
@@ -127,7 +304,156 @@ impl From<(MinecraftAuth,Profile,ExpiringData<MicrosoftAuth>)> for Account{
         Self{
             mc_auth,
             profile,
-            msa
+            msa,
+            ownership:GameOwnership::Unknown,
+        }
+    }
+}
+
+/// The kinds of account the launcher can use.
+///
+/// The Microsoft account will be refreshed when it's expired, and the offline account never expires.
+#[derive(Debug,Clone,Serialize,Deserialize)]
+#[serde(tag = "type")]
+#[allow(clippy::large_enum_variant)] // only a few accounts exist at the same time.
+pub enum AccountKind{
+    /// The account login with Microsoft.
+    Microsoft(ExpiringData<Account>),
+    /// The offline account, see [OfflineAccount].
+    Offline(OfflineAccount),
+    /// The account login with Yggdrasil server, see [YggdrasilAccount].
+    Yggdrasil(YggdrasilAccount),
+}
+
+impl AccountKind{
+
+    /// Get the name of the player.
+    pub fn name(&self) -> &str{
+        match self {
+            AccountKind::Microsoft(x) => &x.data.profile.name,
+            AccountKind::Offline(x) => &x.name,
+            AccountKind::Yggdrasil(x) => &x.profile.name,
+        }
+    }
+
+    /// Get the uuid of the player, without hyphens.
+    pub fn uuid(&self) -> &str{
+        match self {
+            AccountKind::Microsoft(x) => &x.data.profile.id,
+            AccountKind::Offline(x) => &x.uuid,
+            AccountKind::Yggdrasil(x) => &x.profile.id,
+        }
+    }
+
+    /// Get the access token to launch the game.
+    ///
+    /// Return `None` for the offline account, the launcher can pass any placeholder to the game.
+    pub fn access_token(&self) -> Option<&str>{
+        match self {
+            AccountKind::Microsoft(x) => Some(x.data.mc_auth.access_token.expose()),
+            AccountKind::Offline(_) => None,
+            AccountKind::Yggdrasil(x) => Some(x.access_token.expose()),
+        }
+    }
+
+    /// Get the user type to launch the game (`--userType`).
+    pub fn user_type(&self) -> &'static str{
+        match self {
+            AccountKind::Microsoft(_) => "msa",
+            AccountKind::Offline(_) => "legacy",
+            AccountKind::Yggdrasil(_) => "mojang",
+        }
+    }
+
+    /// Check the account is expired or not.
+    ///
+    /// The offline account never expires, and the Yggdrasil token doesn't have an expiry time,
+    /// so they always return `false`.
+    pub fn is_expired(&self) -> bool{
+        match self {
+            AccountKind::Microsoft(x) => x.is_expired(),
+            AccountKind::Offline(_) | AccountKind::Yggdrasil(_) => false,
         }
     }
-}
\ No newline at end of file
+
+    /// Make sure the account can be used to launch the game.
+    ///
+    /// The Microsoft account will be refreshed if it's expired, the Yggdrasil account will be validated
+    /// and refreshed if the token is invalid, and nothing happens for the offline account.
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    /// * `client_id`: The client id of your app.
+    pub async fn ensure_fresh(&mut self, ctx:&HttpContext, client_id:&str) -> Result<(),AuthError>{
+        match self {
+            AccountKind::Microsoft(x) if x.is_expired() => {
+                x.data.renew(ctx,client_id).await?;
+                x.created_at = Local::now();
+            }
+            AccountKind::Yggdrasil(x) if !x.validate(ctx).await? => {
+                x.renew(ctx).await?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Make sure the token is accepted by the server, even if it's revoked before it expires.
+    ///
+    /// The expired Microsoft account is refreshed directly, otherwise the token is checked by
+    /// [Account::ensure_valid]. The Yggdrasil account is validated and refreshed if the token is invalid,
+    /// and the offline account is always valid.
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    /// * `client_id`: The client id of your app.
+    pub async fn ensure_valid(&mut self, ctx:&HttpContext, client_id:&str) -> Result<TokenStatus,AuthError>{
+        let status = match self {
+            AccountKind::Microsoft(x) if x.is_expired() => TokenStatus::from_refresh(x.data.renew(ctx,client_id).await)?,
+            AccountKind::Microsoft(x) => x.data.ensure_valid(ctx,client_id).await?,
+            AccountKind::Yggdrasil(x) if x.validate(ctx).await? => TokenStatus::Valid,
+            AccountKind::Yggdrasil(x) => TokenStatus::from_refresh(x.renew(ctx).await.map_err(AuthError::from))?,
+            AccountKind::Offline(_) => TokenStatus::Valid,
+        };
+        if let (AccountKind::Microsoft(x),TokenStatus::Refreshed) = (self,status) {
+            x.created_at = Local::now();
+        }
+        Ok(status)
+    }
+
+    /// Get the extra JVM arguments the account needs, only the Yggdrasil account needs authlib-injector.
+    ///
+    /// # Arguments
+    /// * `agent_path`: The path of authlib-injector jar.
+    pub fn jvm_args(&self, agent_path:&str) -> Vec<String>{
+        match self {
+            AccountKind::Yggdrasil(x) => x.jvm_args(agent_path),
+            _ => vec![],
+        }
+    }
+
+}
+
+impl From<Account> for AccountKind{
+    fn from(account: Account) -> Self {
+        Self::Microsoft(account.into())
+    }
+}
+
+impl From<ExpiringData<Account>> for AccountKind{
+    fn from(account: ExpiringData<Account>) -> Self {
+        Self::Microsoft(account)
+    }
+}
+
+impl From<OfflineAccount> for AccountKind{
+    fn from(account: OfflineAccount) -> Self {
+        Self::Offline(account)
+    }
+}
+
+impl From<YggdrasilAccount> for AccountKind{
+    fn from(account: YggdrasilAccount) -> Self {
+        Self::Yggdrasil(account)
+    }
+}
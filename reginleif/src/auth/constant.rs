@@ -1,11 +1,18 @@
+pub const AUTHORIZE_URL: &str = "https://login.microsoftonline.com/consumers/oauth2/v2.0/authorize";
 pub const DEVICECODE_URL: &str = "https://login.microsoftonline.com/consumers/oauth2/v2.0/devicecode";
 pub const SCOPE: &str = "XboxLive.signin offline_access";
 pub const GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
 pub const TOKEN_URL: &str = "https://login.microsoftonline.com/consumers/oauth2/v2.0/token";
 pub const REFRESH_GRANT_TYPE: &str = "refresh_token";
+pub const AUTHORIZATION_CODE_GRANT_TYPE: &str = "authorization_code";
 
 pub const XBOX_USER_AUTHENTICATE: &str = "https://user.auth.xboxlive.com/user/authenticate";
 pub const XBOX_XSTS_AUTHORIZE: &str = "https://xsts.auth.xboxlive.com/xsts/authorize";
 pub const MINECRAFT_LOGIN_WITH_XBOX: &str = "https://api.minecraftservices.com/authentication/login_with_xbox";
 pub const MINECRAFT_PROFILE: &str = "https://api.minecraftservices.com/minecraft/profile";
-
+pub const MINECRAFT_ENTITLEMENTS: &str = "https://api.minecraftservices.com/entitlements";
+pub const MINECRAFT_PLAYER: &str = "https://api.minecraftservices.com/player";
+pub const MOJANG_NAME_LOOKUP: &str = "https://api.mojang.com/users/profiles/minecraft";
+pub const MINECRAFT_BULK_LOOKUP: &str = "https://api.minecraftservices.com/minecraft/profile/lookup/bulk/byname";
+pub const SESSION_PROFILE: &str = "https://sessionserver.mojang.com/session/minecraft/profile";
+pub const XBOX_PROFILE_SETTINGS: &str = "https://profile.xboxlive.com/users/me/profile/settings";
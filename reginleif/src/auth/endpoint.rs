@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+use crate::auth::constant::{AUTHORIZE_URL, DEVICECODE_URL, MINECRAFT_BULK_LOOKUP, MINECRAFT_ENTITLEMENTS, MINECRAFT_LOGIN_WITH_XBOX, MINECRAFT_PLAYER, MINECRAFT_PROFILE, MOJANG_NAME_LOOKUP, SESSION_PROFILE, TOKEN_URL, XBOX_PROFILE_SETTINGS, XBOX_USER_AUTHENTICATE, XBOX_XSTS_AUTHORIZE};
+
+/// Auth Endpoints
+///
+/// This struct contains every url that the auth chain (Microsoft -> Xbox -> Minecraft) will request.
+/// The [Default] value points to the production services, you can override any of them to
+/// point the chain to a local mock server or to an alternate/sovereign tenant.
+///
+/// # Example
+/// ```no_run
+/// use reginleif::auth::endpoint::AuthEndpoints;
+///
+/// let production = AuthEndpoints::default();
+///
+/// let mock = AuthEndpoints{
+///     minecraft_profile: "http://127.0.0.1:8080/minecraft/profile".to_string(),
+///     ..AuthEndpoints::default()
+/// };
+///
+/// let organizations = AuthEndpoints::default()
+///     .with_microsoft_authority("https://login.microsoftonline.com/organizations");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AuthEndpoints {
+    /// The url the user visits in the browser in [AuthCodeLogin](crate::auth::microsoft::auth_code::AuthCodeLogin).
+    pub authorize: String,
+    /// The url to fetch [DeviceCode](crate::auth::microsoft::DeviceCode).
+    pub device_code: String,
+    /// The url to exchange and refresh [MicrosoftAuth](crate::auth::microsoft::MicrosoftAuth).
+    pub token: String,
+    /// The url to fetch [XboxLiveToken](crate::auth::xbox::XboxLiveToken).
+    pub xbox_user_authenticate: String,
+    /// The url to fetch [XboxSecurityToken](crate::auth::xbox::XboxSecurityToken).
+    pub xbox_xsts_authorize: String,
+    /// The url to fetch [XboxProfile](crate::auth::xbox::profile::XboxProfile).
+    pub xbox_profile: String,
+    /// The url to fetch [MinecraftAuth](crate::auth::minecraft::MinecraftAuth).
+    pub minecraft_login_with_xbox: String,
+    /// The url to fetch [Profile](crate::auth::minecraft::Profile).
+    pub minecraft_profile: String,
+    /// The base url to fetch [Entitlements](crate::auth::minecraft::entitlement::Entitlements).
+    pub minecraft_entitlements: String,
+    /// The base url to fetch [PlayerCertificates](crate::auth::minecraft::certificate::PlayerCertificates)
+    /// and [PlayerAttributes](crate::auth::minecraft::attribute::PlayerAttributes).
+    pub minecraft_player: String,
+    /// The base url to look up the uuid by the name in [ProfileLookup](crate::auth::minecraft::lookup::ProfileLookup).
+    pub mojang_name_lookup: String,
+    /// The url to look up the uuids by the names in [ProfileLookup](crate::auth::minecraft::lookup::ProfileLookup).
+    pub minecraft_bulk_lookup: String,
+    /// The base url to fetch [PlayerProfile](crate::auth::minecraft::lookup::PlayerProfile) by the uuid.
+    pub session_profile: String,
+}
+
+impl Default for AuthEndpoints {
+    /// The production endpoints.
+    fn default() -> Self {
+        Self {
+            authorize: AUTHORIZE_URL.to_string(),
+            device_code: DEVICECODE_URL.to_string(),
+            token: TOKEN_URL.to_string(),
+            xbox_user_authenticate: XBOX_USER_AUTHENTICATE.to_string(),
+            xbox_xsts_authorize: XBOX_XSTS_AUTHORIZE.to_string(),
+            xbox_profile: XBOX_PROFILE_SETTINGS.to_string(),
+            minecraft_login_with_xbox: MINECRAFT_LOGIN_WITH_XBOX.to_string(),
+            minecraft_profile: MINECRAFT_PROFILE.to_string(),
+            minecraft_entitlements: MINECRAFT_ENTITLEMENTS.to_string(),
+            minecraft_player: MINECRAFT_PLAYER.to_string(),
+            mojang_name_lookup: MOJANG_NAME_LOOKUP.to_string(),
+            minecraft_bulk_lookup: MINECRAFT_BULK_LOOKUP.to_string(),
+            session_profile: SESSION_PROFILE.to_string(),
+        }
+    }
+}
+
+impl AuthEndpoints {
+
+    /// Replace the Microsoft OAuth2.0 endpoints with the given authority.
+    ///
+    /// # Arguments
+    /// * `authority`: The authority url, like `https://login.microsoftonline.com/consumers`.
+    pub fn with_microsoft_authority(mut self, authority: &str) -> Self {
+        let authority = authority.trim_end_matches('/');
+        self.authorize = format!("{}/oauth2/v2.0/authorize", authority);
+        self.device_code = format!("{}/oauth2/v2.0/devicecode", authority);
+        self.token = format!("{}/oauth2/v2.0/token", authority);
+        self
+    }
+
+}
@@ -0,0 +1,182 @@
+use std::time::Duration;
+use reqwest::{Response, StatusCode};
+use thiserror::Error;
+use crate::auth::microsoft::MicrosoftAuthError;
+use crate::auth::minecraft::name::NameAvailability;
+use crate::auth::rate_limit::retry_after;
+use crate::auth::xbox::XboxSecurityError;
+use crate::auth::yggdrasil::YggdrasilError;
+
+/// The error returned by Minecraft services (api.minecraftservices.com).
+#[derive(Error, Debug)]
+pub enum MinecraftServicesError {
+
+    /// The Minecraft access token is invalid or expired, you should refresh the account.
+    #[error("The Minecraft access token is invalid or expired. (401)")]
+    Unauthorized,
+
+    /// The request is forbidden, like the Xbox token is not allowed to login Minecraft.
+    ///
+    /// You can check error details in the inner string.
+    #[error("The request is forbidden by Minecraft services. (403) details:{0}")]
+    Forbidden(String),
+
+    /// Too many requests, you should try again after `retry_after`.
+    #[error("Too many requests to Minecraft services. (429) retry after: {retry_after:?}")]
+    TooManyRequests {
+        /// The `Retry-After` header of the response, if it exists.
+        retry_after: Option<Duration>,
+    },
+
+    /// The name can't be used to create the profile or change the name.
+    #[error("The name can't be used. status:{0:?}")]
+    NameUnavailable(NameAvailability),
+
+}
+
+/// The error that can occur in the whole login chain (Microsoft -> Xbox -> Minecraft).
+///
+/// You can use [is_retryable](AuthError::is_retryable) and [needs_relogin](AuthError::needs_relogin)
+/// to decide what to do next.
+#[derive(Error, Debug)]
+pub enum AuthError {
+
+    /// Error while sending the request, like timeout or connection refused.
+    ///
+    /// You can check error details in the inner error.
+    #[error("Network error. details:{0}")]
+    Network(reqwest::Error),
+
+    /// The server responds an unexpected HTTP status.
+    #[error("Unexpected HTTP status {status} from {url}. body:{body}")]
+    Http {
+        /// The url of the request.
+        url: String,
+        /// The status of the response.
+        status: StatusCode,
+        /// The body of the response.
+        body: String,
+    },
+
+    /// Error from Microsoft OAuth2.0 service.
+    #[error(transparent)]
+    Microsoft(#[from] MicrosoftAuthError),
+
+    /// Error from Xbox Security Token Service.
+    #[error(transparent)]
+    Xbox(#[from] XboxSecurityError),
+
+    /// Error from Minecraft services.
+    #[error(transparent)]
+    Minecraft(#[from] MinecraftServicesError),
+
+    /// Error from Yggdrasil server.
+    #[error(transparent)]
+    Yggdrasil(#[from] YggdrasilError),
+
+    /// The account doesn't have a Minecraft profile, which means the user doesn't own the game
+    /// or hasn't created the profile yet.
+    #[error("The account doesn't have a Minecraft profile.")]
+    ProfileNotFound,
+
+    /// The response can't be parsed.
+    ///
+    /// You can check error details in the inner string.
+    #[error("Error while parsing response. details:{0}")]
+    InvalidResponse(String),
+
+}
+
+impl From<reqwest::Error> for AuthError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_decode() {
+            Self::InvalidResponse(e.to_string())
+        } else {
+            Self::Network(e)
+        }
+    }
+}
+
+impl AuthError {
+
+    /// Whether the same request may succeed if you try again later.
+    ///
+    /// The reqwest errors wrapped by the services are only retryable if the connection failed or timed out,
+    /// the others (like the body can't be decoded) will fail again.
+    pub fn is_retryable(&self) -> bool {
+        let is_transport = |e: &reqwest::Error| e.is_connect() || e.is_timeout();
+        match self {
+            AuthError::Network(_) => true,
+            AuthError::Http { status, .. } => status.is_server_error(),
+            AuthError::Microsoft(e) => match e {
+                MicrosoftAuthError::AuthorizationPending | MicrosoftAuthError::SlowDown => true,
+                MicrosoftAuthError::ReqwesetError(e) => is_transport(e),
+                _ => false,
+            },
+            AuthError::Xbox(e) => match e {
+                XboxSecurityError::Status { status, .. } => status.is_server_error(),
+                XboxSecurityError::ReqwestError(e) => is_transport(e),
+                _ => false,
+            },
+            AuthError::Minecraft(e) => matches!(e, MinecraftServicesError::TooManyRequests { .. }),
+            AuthError::Yggdrasil(YggdrasilError::ReqwestError(e)) => is_transport(e),
+            AuthError::Yggdrasil(_) => false,
+            AuthError::ProfileNotFound | AuthError::InvalidResponse(_) => false,
+        }
+    }
+
+    /// Whether the user must login again, because the Microsoft or Yggdrasil token can't be refreshed anymore.
+    pub fn needs_relogin(&self) -> bool {
+        matches!(
+            self,
+            AuthError::Microsoft(
+                MicrosoftAuthError::InvalidGrant
+                    | MicrosoftAuthError::AuthorizationDeclined
+                    | MicrosoftAuthError::ExpiredToken
+                    | MicrosoftAuthError::BadVerificationCode
+            ) | AuthError::Yggdrasil(YggdrasilError::ForbiddenOperation(_))
+        )
+    }
+
+    /// Return the response if the status is success, otherwise read the body into [AuthError::Http].
+    pub(crate) async fn check_status(res: Response) -> Result<Response, AuthError> {
+        if res.status().is_success() {
+            return Ok(res);
+        }
+        Err(Self::from_status(res).await)
+    }
+
+    /// Like [check_status](AuthError::check_status), but map the status of Minecraft services
+    /// to [MinecraftServicesError].
+    pub(crate) async fn check_minecraft(res: Response) -> Result<Response, AuthError> {
+        match res.status() {
+            status if status.is_success() => Ok(res),
+            StatusCode::UNAUTHORIZED => Err(MinecraftServicesError::Unauthorized.into()),
+            StatusCode::FORBIDDEN => Err(MinecraftServicesError::Forbidden(res.text().await?).into()),
+            StatusCode::TOO_MANY_REQUESTS => {
+                Err(MinecraftServicesError::TooManyRequests { retry_after: retry_after(&res) }.into())
+            }
+            _ => Err(Self::from_status(res).await),
+        }
+    }
+
+    /// Like [check_minecraft](AuthError::check_minecraft), but for the endpoints without the access token,
+    /// so 401 and 403 are kept as [AuthError::Http] instead of the token errors.
+    pub(crate) async fn check_public(res: Response) -> Result<Response, AuthError> {
+        match res.status() {
+            status if status.is_success() => Ok(res),
+            StatusCode::TOO_MANY_REQUESTS => {
+                Err(MinecraftServicesError::TooManyRequests { retry_after: retry_after(&res) }.into())
+            }
+            _ => Err(Self::from_status(res).await),
+        }
+    }
+
+    async fn from_status(res: Response) -> AuthError {
+        let url = res.url().to_string();
+        let status = res.status();
+        let body = res.text().await.unwrap_or_default();
+        AuthError::Http { url, status, body }
+    }
+
+}
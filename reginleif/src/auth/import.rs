@@ -0,0 +1,353 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+use chrono::{DateTime, Local, TimeZone};
+use serde::Deserialize;
+use thiserror::Error;
+use reginleif_utils::expiring_data::ExpiringData;
+use reginleif_utils::secret::SecretString;
+use crate::auth::account::{Account, AccountKind};
+use crate::auth::constant::SCOPE;
+use crate::auth::error::AuthError;
+use crate::auth::microsoft::MicrosoftAuth;
+use crate::auth::minecraft::{Caps, MinecraftAuth, Profile, Skin};
+use crate::auth::minecraft::entitlement::GameOwnership;
+use crate::auth::offline::OfflineAccount;
+use crate::http::HttpContext;
+
+/// The error that can occur while importing the accounts from other launchers.
+#[derive(Error, Debug)]
+pub enum ImportError {
+    /// The accounts file can't be read.
+    #[error("Error while reading the accounts file. details:{0}")]
+    Io(#[from] std::io::Error),
+    /// The accounts file or the entry is not in the expected format.
+    #[error("Error while parsing the accounts file. details:{0}")]
+    Parse(#[from] serde_json::Error),
+    /// The type of the account can't be imported, like the legacy Mojang account.
+    #[error("The account type {0} is not supported.")]
+    Unsupported(String),
+    /// The token is expired and there is no refresh token, the user must login again.
+    #[error("The token is expired and can't be refreshed, please login again.")]
+    Expired,
+    /// The entry doesn't have a Minecraft profile.
+    #[error("The account doesn't have a Minecraft profile.")]
+    NoProfile,
+    /// Error while refreshing the account.
+    #[error(transparent)]
+    Auth(#[from] AuthError),
+}
+
+/// The entry that can't be imported.
+#[derive(Debug)]
+pub struct ImportFailure {
+    /// The name of the player or the id of the entry, to tell the user which account is failed.
+    pub name: String,
+    pub error: ImportError,
+}
+
+/// The result of importing, the entries are imported one by one,
+/// so one broken entry won't stop the others.
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// The imported accounts, you can add them to [AccountManager](crate::auth::manager::AccountManager).
+    pub accounts: Vec<AccountKind>,
+    /// The entries that can't be imported.
+    pub failed: Vec<ImportFailure>,
+}
+
+impl ImportReport {
+
+    fn push(&mut self, name: String, result: Result<AccountKind, ImportError>) {
+        match result {
+            Ok(account) => self.accounts.push(account),
+            Err(error) => self.failed.push(ImportFailure { name, error }),
+        }
+    }
+
+}
+
+/// A token stored by Prism Launcher, `exp` and `iat` are unix timestamps.
+#[derive(Debug, Deserialize)]
+struct PrismToken {
+    token: Option<SecretString>,
+    refresh_token: Option<SecretString>,
+    iat: Option<i64>,
+    exp: Option<i64>,
+}
+
+impl PrismToken {
+
+    fn issued_at(&self) -> DateTime<Local> {
+        self.iat.and_then(|x| Local.timestamp_opt(x, 0).single()).unwrap_or_else(Local::now)
+    }
+
+    fn expires_in(&self) -> Duration {
+        match (self.iat, self.exp) {
+            (Some(iat), Some(exp)) => Duration::from_secs(exp.saturating_sub(iat).max(0) as u64),
+            _ => Duration::ZERO,
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.exp.is_none_or(|exp| Local::now().timestamp() >= exp)
+    }
+
+}
+
+#[derive(Debug, Deserialize)]
+struct PrismSkin {
+    id: String,
+    url: String,
+    variant: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PrismCape {
+    id: String,
+    url: String,
+    alias: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PrismProfile {
+    id: String,
+    name: String,
+    skin: Option<PrismSkin>,
+    #[serde(default)]
+    capes: Vec<PrismCape>,
+    /// The id of the active cape.
+    cape: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PrismEntitlement {
+    #[serde(default)]
+    owns_minecraft: bool,
+    #[serde(default)]
+    can_play_minecraft: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct PrismAccount {
+    #[serde(rename = "type")]
+    kind: String,
+    msa: Option<PrismToken>,
+    #[serde(rename = "msa-client-id")]
+    msa_client_id: Option<String>,
+    ygg: Option<PrismToken>,
+    profile: Option<PrismProfile>,
+    entitlement: Option<PrismEntitlement>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PrismAccounts {
+    accounts: Vec<serde_json::Value>,
+}
+
+impl From<PrismProfile> for Profile {
+    fn from(profile: PrismProfile) -> Self {
+        let skins = profile.skin.into_iter()
+            .filter(|x| !x.url.is_empty())
+            .map(|x| Skin {
+                texture_key: x.url.rsplit('/').next().unwrap_or_default().to_string(),
+                id: x.id,
+                state: "ACTIVE".to_string(),
+                url: x.url,
+                variant: x.variant,
+            })
+            .collect();
+
+        let capes = profile.capes.into_iter()
+            .map(|x| Caps {
+                state: if profile.cape.as_deref() == Some(x.id.as_str()) { "ACTIVE" } else { "INACTIVE" }.to_string(),
+                id: x.id,
+                url: x.url,
+                alias: x.alias,
+            })
+            .collect();
+
+        Self { id: profile.id, name: profile.name, skins, capes }
+    }
+}
+
+impl PrismEntitlement {
+    fn ownership(&self) -> GameOwnership {
+        match (self.owns_minecraft, self.can_play_minecraft) {
+            (true, _) => GameOwnership::Purchased,
+            (false, true) => GameOwnership::GamePass,
+            (false, false) => GameOwnership::NotOwned,
+        }
+    }
+}
+
+/// Import the accounts from `accounts.json` of Prism Launcher or MultiMC.
+///
+/// The Microsoft account whose Minecraft token is still valid is imported as is, otherwise it's refreshed
+/// by [Account::renew] with the refresh token. The refresh token is bound to the client id which requested it,
+/// so the `msa-client-id` of the entry is used if it exists, and the later refresh with another client id may fail.
+///
+/// The offline accounts are imported too, and the legacy Mojang accounts are reported as unsupported.
+///
+/// # Arguments
+/// * `path`: The path of `accounts.json`.
+/// * `ctx`: The HTTP context.
+/// * `client_id`: The client id to refresh the token if the entry doesn't have `msa-client-id`.
+pub async fn import_prism(path: impl AsRef<Path>, ctx: &HttpContext, client_id: &str) -> Result<ImportReport, ImportError> {
+    let content = tokio::fs::read_to_string(path).await?;
+    let file: PrismAccounts = serde_json::from_str(&content)?;
+
+    let mut report = ImportReport::default();
+    for value in file.accounts {
+        let name = value["profile"]["name"].as_str().unwrap_or("unknown").to_string();
+        let result = match serde_json::from_value::<PrismAccount>(value) {
+            Ok(entry) => import_prism_entry(entry, ctx, client_id).await,
+            Err(e) => Err(e.into()),
+        };
+        report.push(name, result);
+    }
+    Ok(report)
+}
+
+async fn import_prism_entry(entry: PrismAccount, ctx: &HttpContext, client_id: &str) -> Result<AccountKind, ImportError> {
+    match entry.kind.as_str() {
+        "MSA" => {}
+        "Offline" => {
+            let profile = entry.profile.ok_or(ImportError::NoProfile)?;
+            return Ok(OfflineAccount { name: profile.name, uuid: profile.id }.into());
+        }
+        other => return Err(ImportError::Unsupported(other.to_string())),
+    }
+
+    let profile = entry.profile.ok_or(ImportError::NoProfile)?;
+    let msa = entry.msa.ok_or(ImportError::Expired)?;
+    let ygg = entry.ygg.ok_or(ImportError::Expired)?;
+
+    let refresh_token = msa.refresh_token.clone().unwrap_or_default();
+    let mc_token = ygg.token.clone().unwrap_or_default();
+    let usable = !mc_token.is_empty() && !ygg.is_expired();
+
+    if !usable && refresh_token.is_empty() {
+        return Err(ImportError::Expired);
+    }
+
+    let msa = ExpiringData {
+        data: MicrosoftAuth {
+            token_type: "Bearer".to_string(),
+            scope: SCOPE.to_string(),
+            expires_in: msa.expires_in(),
+            access_token: msa.token.clone().unwrap_or_default(),
+            refresh_token,
+        },
+        created_at: msa.issued_at(),
+    };
+
+    let mc_auth = MinecraftAuth {
+        username: profile.id.clone(),
+        access_token: mc_token,
+        expires_in: ygg.expires_in(),
+        token_type: "Bearer".to_string(),
+    };
+
+    let mut account = Account {
+        mc_auth,
+        profile: profile.into(),
+        msa,
+        ownership: entry.entitlement.map(|x| x.ownership()).unwrap_or_default(),
+    };
+
+    if usable {
+        return Ok(ExpiringData { data: account, created_at: ygg.issued_at() }.into());
+    }
+
+    let client_id = entry.msa_client_id.as_deref().unwrap_or(client_id);
+    account.renew(ctx, client_id).await?;
+    Ok(account.into())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OfficialProfile {
+    id: String,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OfficialAccount {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    access_token: SecretString,
+    access_token_expires_at: Option<String>,
+    minecraft_profile: Option<OfficialProfile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OfficialAccounts {
+    accounts: HashMap<String, serde_json::Value>,
+}
+
+/// Import the accounts from `launcher_accounts.json` of the official launcher.
+///
+/// The official launcher doesn't store the Microsoft refresh token in the file, so only the accounts
+/// whose Minecraft token is still valid can be imported, and the user must login again after the token expires
+/// (the refresh will fail with [AuthError::needs_relogin]).
+///
+/// # Arguments
+/// * `path`: The path of `launcher_accounts.json`.
+pub async fn import_official(path: impl AsRef<Path>) -> Result<ImportReport, ImportError> {
+    let content = tokio::fs::read_to_string(path).await?;
+    let file: OfficialAccounts = serde_json::from_str(&content)?;
+
+    let mut report = ImportReport::default();
+    for (id, value) in file.accounts {
+        let name = value["minecraftProfile"]["name"].as_str().map(str::to_string).unwrap_or(id);
+        let result = serde_json::from_value::<OfficialAccount>(value)
+            .map_err(ImportError::from)
+            .and_then(import_official_entry);
+        report.push(name, result);
+    }
+    Ok(report)
+}
+
+fn import_official_entry(entry: OfficialAccount) -> Result<AccountKind, ImportError> {
+    if entry.kind != "Xbox" {
+        return Err(ImportError::Unsupported(entry.kind));
+    }
+
+    let profile = entry.minecraft_profile.ok_or(ImportError::NoProfile)?;
+    let expires_at = entry.access_token_expires_at
+        .and_then(|x| DateTime::parse_from_rfc3339(&x).ok())
+        .map(|x| x.with_timezone(&Local))
+        .ok_or(ImportError::Expired)?;
+
+    let now = Local::now();
+    if entry.access_token.is_empty() || expires_at <= now {
+        return Err(ImportError::Expired);
+    }
+
+    let account = Account {
+        mc_auth: MinecraftAuth {
+            username: profile.id.clone(),
+            access_token: entry.access_token,
+            expires_in: (expires_at - now).to_std().unwrap_or_default(),
+            token_type: "Bearer".to_string(),
+        },
+        profile: Profile { id: profile.id, name: profile.name, skins: vec![], capes: vec![] },
+        msa: ExpiringData {
+            data: MicrosoftAuth {
+                token_type: "Bearer".to_string(),
+                scope: SCOPE.to_string(),
+                expires_in: Duration::ZERO,
+                access_token: Default::default(),
+                refresh_token: Default::default(),
+            },
+            created_at: now,
+        },
+        ownership: GameOwnership::Unknown,
+    };
+
+    Ok(ExpiringData { data: account, created_at: now }.into())
+}
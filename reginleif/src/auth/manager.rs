@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use std::collections::hash_map::{Iter, Values};
+use std::io::ErrorKind;
+use std::marker::PhantomData;
+use serde::{Deserialize, Serialize};
+use reginleif_macro::Storage;
+use reginleif_utils::encrypted_store::{EncryptedStore, StoreKey};
+use reginleif_utils::save_path::{BaseStorePoint, Store};
+use crate::auth::account::AccountKind;
+use crate::auth::error::AuthError;
+use crate::http::HttpContext;
+
+/// Account Manager
+///
+/// This struct holds all the accounts of the launcher keyed by the uuid of the player,
+/// and remembers which one is selected.
+///
+/// It implements [Store], so you can save it to and load it from `accounts.json` under the base path.
+/// The accounts contain the tokens, so you'd better save it with
+/// [EncryptedStore](reginleif_utils::encrypted_store::EncryptedStore) instead.
+///
+/// # Example
+/// ```no_run
+/// use std::path::PathBuf;
+/// use reginleif::auth::manager::AccountManager;
+/// use reginleif::auth::offline::OfflineAccount;
+/// use reginleif::http::HttpContext;
+/// use reginleif_macro::BaseStorePoint;
+/// use reginleif_utils::save_path::Store;
+///
+/// #[derive(BaseStorePoint,Clone,Debug)]
+/// struct LauncherPath(PathBuf);
+///
+/// async fn example(){
+///     let base = LauncherPath(PathBuf::from("launcher"));
+///     let mut manager = AccountManager::load_or_default(&base).unwrap();
+///
+///     manager.add(OfflineAccount::new("Notch"));
+///     let failed = manager.refresh_all(&HttpContext::default(),"your_client_id").await;
+///     println!("{:?}",failed);
+///
+///     manager.save(&base).unwrap();
+/// }
+/// ```
+#[derive(Debug,Clone,Serialize,Deserialize,Storage)]
+#[filepath(&["accounts.json"])]
+pub struct AccountManager<T> where T:BaseStorePoint{
+    accounts:HashMap<String,AccountKind>,
+    selected:Option<String>,
+    #[serde(skip)]
+    _t:PhantomData<T>,
+}
+
+impl<T> Default for AccountManager<T> where T:BaseStorePoint{
+    fn default() -> Self {
+        Self{
+            accounts:HashMap::new(),
+            selected:None,
+            _t:PhantomData,
+        }
+    }
+}
+
+impl<T> AccountManager<T> where T:BaseStorePoint{
+
+    /// Create an empty manager.
+    pub fn new() -> Self{
+        Self::default()
+    }
+
+    /// Load the manager from the base path, or create an empty one if the file doesn't exist.
+    ///
+    /// The other errors (like the file is broken) are returned, so the saved accounts
+    /// won't be overwritten by an empty manager.
+    ///
+    /// # Arguments
+    /// * `base`: the base path of the data.
+    pub fn load_or_default(base:&T) -> anyhow::Result<Self>{
+        Self::or_default(Self::load(base))
+    }
+
+    /// Load the manager saved by [save_encrypted](EncryptedStore::save_encrypted), or create an empty one if the file doesn't exist.
+    ///
+    /// The plaintext file is migrated like [load_encrypted](EncryptedStore::load_encrypted),
+    /// and the other errors (like the key is wrong) are returned.
+    ///
+    /// # Arguments
+    /// * `base`: the base path of the data.
+    /// * `key`: the key to decrypt the data.
+    pub fn load_or_default_encrypted(base:&T, key:&StoreKey) -> anyhow::Result<Self>{
+        Self::or_default(Self::load_encrypted(base,key))
+    }
+
+    fn or_default(loaded:anyhow::Result<Self>) -> anyhow::Result<Self>{
+        match loaded {
+            Err(e) if e.downcast_ref::<std::io::Error>().is_some_and(|x| x.kind() == ErrorKind::NotFound) => Ok(Self::default()),
+            loaded => loaded,
+        }
+    }
+
+    /// Add the account, the old one with the same uuid will be replaced and returned.
+    ///
+    /// If no account is selected, the added account will be selected.
+    ///
+    /// # Arguments
+    /// * `account`: The account to add.
+    pub fn add(&mut self, account:impl Into<AccountKind>) -> Option<AccountKind>{
+        let account = account.into();
+        let uuid = account.uuid().to_string();
+        if self.selected.is_none() {
+            self.selected = Some(uuid.clone());
+        }
+        self.accounts.insert(uuid,account)
+    }
+
+    /// Remove the account, if it's selected, no account will be selected after removing.
+    ///
+    /// # Arguments
+    /// * `uuid`: The uuid of the player.
+    pub fn remove(&mut self, uuid:&str) -> Option<AccountKind>{
+        if self.selected.as_deref() == Some(uuid) {
+            self.selected = None;
+        }
+        self.accounts.remove(uuid)
+    }
+
+    /// Select the account, return `false` if the account doesn't exist.
+    ///
+    /// # Arguments
+    /// * `uuid`: The uuid of the player.
+    pub fn select(&mut self, uuid:&str) -> bool{
+        if !self.accounts.contains_key(uuid) {
+            return false;
+        }
+        self.selected = Some(uuid.to_string());
+        true
+    }
+
+    /// Get the selected account.
+    pub fn selected(&self) -> Option<&AccountKind>{
+        self.accounts.get(self.selected.as_deref()?)
+    }
+
+    /// Get the mutable reference of the selected account.
+    pub fn selected_mut(&mut self) -> Option<&mut AccountKind>{
+        self.accounts.get_mut(self.selected.as_deref()?)
+    }
+
+    /// Get the account by the uuid of the player.
+    pub fn get(&self, uuid:&str) -> Option<&AccountKind>{
+        self.accounts.get(uuid)
+    }
+
+    /// Get the mutable reference of the account by the uuid of the player.
+    pub fn get_mut(&mut self, uuid:&str) -> Option<&mut AccountKind>{
+        self.accounts.get_mut(uuid)
+    }
+
+    /// Iterate the uuid and the account.
+    pub fn iter(&self) -> Iter<'_, String, AccountKind>{
+        self.accounts.iter()
+    }
+
+    /// Iterate the accounts.
+    pub fn accounts(&self) -> Values<'_, String, AccountKind>{
+        self.accounts.values()
+    }
+
+    /// The number of the accounts.
+    pub fn len(&self) -> usize{
+        self.accounts.len()
+    }
+
+    /// Whether there is no account.
+    pub fn is_empty(&self) -> bool{
+        self.accounts.is_empty()
+    }
+
+    /// Make sure all the accounts can be used, see [AccountKind::ensure_fresh].
+    ///
+    /// The accounts are refreshed one by one, and the failed ones are kept as is.
+    /// Return the uuid and the error of the failed accounts, you can use [AuthError::needs_relogin]
+    /// to decide whether to ask the user to login again.
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    /// * `client_id`: The client id of your app.
+    pub async fn refresh_all(&mut self, ctx:&HttpContext, client_id:&str) -> Vec<(String,AuthError)>{
+        let mut failed = vec![];
+        for (uuid,account) in self.accounts.iter_mut() {
+            if let Err(e) = account.ensure_fresh(ctx,client_id).await {
+                failed.push((uuid.clone(),e));
+            }
+        }
+        failed
+    }
+
+}
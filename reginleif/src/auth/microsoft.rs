@@ -1,14 +1,23 @@
 use std::collections::HashMap;
 use std::time::Duration;
 use async_trait::async_trait;
-use reqwest::Client;
+use reqwest::Response;
 use reqwest::header::CONTENT_TYPE;
 use serde_json::Value;
 use reginleif_macro::{Expirable, NoRefresh};
+use reginleif_utils::secret::SecretString;
 use reginleif_utils::serde_convert::{duration_to_sec, sec_to_duration};
 use thiserror::Error;
-use crate::auth::constant::{DEVICECODE_URL, GRANT_TYPE, REFRESH_GRANT_TYPE, SCOPE, TOKEN_URL};
+use crate::auth::constant::{GRANT_TYPE, REFRESH_GRANT_TYPE, SCOPE};
+use crate::auth::error::AuthError;
 use reginleif_utils::expiring_data::{ExpiringData, Refreshable};
+use crate::auth::rate_limit::SendLimited;
+use crate::http::HttpContext;
+
+/// A driver of the device code flow, which polls the token for you.
+pub mod device_login;
+/// The authorization code flow with PKCE, which captures the redirect by a loopback listener.
+pub mod auth_code;
 
 /// The struct contain all the information that oauth2 device code auth flow need.
 /// 
@@ -56,6 +65,12 @@ pub enum MicrosoftAuthError{
     #[error("Failed to exchange device code. please try again. details:AuthorizationPending")]
     AuthorizationPending,
 
+    /// The server asks the client to poll slower, you should add 5 seconds to ``interval`` and try again.
+    ///
+    /// see [RFC 8628](https://datatracker.ietf.org/doc/html/rfc8628#section-3.5)
+    #[error("Failed to exchange device code. please try again later. details:SlowDown")]
+    SlowDown,
+
     /// User declined the authorization.
     ///
     /// see [Microsoft DOCS](https://learn.microsoft.com/en-us/entra/identity-platform/v2-oauth2-device-code#expected-errors)
@@ -74,6 +89,18 @@ pub enum MicrosoftAuthError{
     #[error("Failed to exchange device code. details: ExpiredToken")]
     ExpiredToken,
 
+    /// The refresh token is expired or revoked, you should ask the user to login again.
+    ///
+    /// see [Microsoft DOCS](https://learn.microsoft.com/en-us/entra/identity-platform/reference-error-codes#handling-error-codes-in-your-application)
+    #[error("The refresh token is expired or revoked, please login again. details: InvalidGrant")]
+    InvalidGrant,
+
+    /// This error is related to the loopback listener which captures the redirect.
+    ///
+    /// You can check error details in the inner error.
+    #[error("Error while listening the redirect. details:{0}")]
+    IoError(#[from] std::io::Error),
+
     /// This error is related to the request error.
     ///
     /// You can check error details in the inner error.
@@ -93,22 +120,22 @@ impl DeviceCode{
     /// To fetch the device code from the auth server.
     ///
     /// # Arguments
-    /// * `client`: The reqwest client.
+    /// * `ctx`: The HTTP context, use [HttpContext::default] for production.
     /// * `client_id`: The client id of your app.
     ///
     /// # Returns
-    /// * Return anyhow::Result<DeviceCode>
+    /// * Return Result<DeviceCode,AuthError>
     ///
     /// # Example
     /// ```no_run
-    /// use reqwest::Client;
+    /// use reginleif::http::HttpContext;
     /// use reginleif::auth::microsoft::DeviceCode;
     ///
     /// #[tokio::main]
     /// async fn main(){
-    ///     let client = Client::new();
+    ///     let ctx = HttpContext::default();
     ///     let client_id = "your_client_id";
-    ///     let res = DeviceCode::fetch(&client,client_id).await;
+    ///     let res = DeviceCode::fetch(&ctx,client_id).await;
     ///
     ///     match res{
     ///         Ok(device_code) => {
@@ -121,26 +148,27 @@ impl DeviceCode{
     ///     };
     /// }
     /// ```
-    pub async fn fetch(client: &Client, client_id: &str) -> anyhow::Result<Self>{
+    pub async fn fetch(ctx: &HttpContext, client_id: &str) -> Result<Self,AuthError>{
         let params = HashMap::from([
             ("client_id", client_id),
             ("scope", SCOPE)
         ]);
-        let res = client.post(DEVICECODE_URL)
+        let res = ctx.client.post(&ctx.endpoints.device_code)
             .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
             .form(&params)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<DeviceCode>()
+            .send_limited(ctx)
             .await?;
-        Ok(res)
+
+        if !res.status().is_success() {
+            return Err(MicrosoftAuthError::from_response(res).await?.into());
+        }
+        Ok(res.json::<DeviceCode>().await?)
     }
 
     /// To exchange the device code to a valid token.
     ///
     /// # Arguments
-    /// * `client`: The reqwest client.
+    /// * `ctx`: The HTTP context, use [HttpContext::default] for production.
     /// * `client_id`: The client id of your app.
     ///
     /// # Returns
@@ -148,15 +176,15 @@ impl DeviceCode{
     ///
     /// # Example
     /// ```no_run
-    /// use reqwest::Client;
+    /// use reginleif::http::HttpContext;
     /// use reginleif::auth::microsoft::DeviceCode;
     /// use reginleif::auth::microsoft::{MicrosoftAuthError, MicrosoftAuth};
     ///
     /// #[tokio::main]
     /// async fn main(){
-    /// let client = Client::new();
+    /// let ctx = HttpContext::default();
     ///     let client_id = "your_client_id";
-    ///     let res = DeviceCode::fetch(&client,client_id).await;
+    ///     let res = DeviceCode::fetch(&ctx,client_id).await;
     ///
     ///     let device_code = match res{
     ///         Ok(device_code) => {
@@ -170,7 +198,7 @@ impl DeviceCode{
     ///     };
     ///
     ///     let res = loop{
-    ///         let result = device_code.exchange(&client,client_id).await;
+    ///         let result = device_code.exchange(&ctx,client_id).await;
     ///         let res = match result{
     ///             Ok(res) => {res}
     ///             Err(e) => {
@@ -189,7 +217,7 @@ impl DeviceCode{
     ///
     /// }
     /// ```
-    pub async fn exchange(&self, client: &Client, client_id:&str) -> Result<ExpiringData<MicrosoftAuth>,MicrosoftAuthError>{
+    pub async fn exchange(&self, ctx: &HttpContext, client_id:&str) -> Result<ExpiringData<MicrosoftAuth>,MicrosoftAuthError>{
 
         let params = HashMap::from([
             (String::from("client_id"), client_id.to_string()),
@@ -197,31 +225,53 @@ impl DeviceCode{
             (String::from("device_code"), self.device_code.to_string()),
         ]);
 
-        let res = client.post(TOKEN_URL)
+        let res = ctx.client.post(&ctx.endpoints.token)
             .form(&params)
-            .send()
+            .send_limited(ctx)
             .await?;
 
         if res.status().is_success() {
             Ok(res.json::<MicrosoftAuth>().await?.into())
         } else {
-            match res.json::<Value>()
-                .await?
-                .get("error")
-                .ok_or(MicrosoftAuthError::Others("Error while reading error field".to_string()))?
-                .as_str()
-                .ok_or(MicrosoftAuthError::Others("Error while convert error field to str".to_string()))?
-            {
-                "authorization_pending" => Err(MicrosoftAuthError::AuthorizationPending),
-                "authorization_declined" => Err(MicrosoftAuthError::AuthorizationDeclined),
-                "bad_verification_code" => Err(MicrosoftAuthError::BadVerificationCode),
-                "expired_token" => Err(MicrosoftAuthError::ExpiredToken),
-                _other => Err(MicrosoftAuthError::Others(
-                    format!("Unknown error: {:?}", _other)
-                )),
-            }
+            Err(MicrosoftAuthError::from_response(res).await?)
+        }
+
+    }
+
+}
+
+impl MicrosoftAuthError{
+
+    /// Convert the OAuth2.0 `error` code to the error.
+    ///
+    /// see [Microsoft DOCS](https://learn.microsoft.com/en-us/entra/identity-platform/v2-oauth2-device-code#expected-errors)
+    fn from_error_code(code:&str) -> Self{
+        match code {
+            "authorization_pending" => MicrosoftAuthError::AuthorizationPending,
+            "slow_down" => MicrosoftAuthError::SlowDown,
+            "authorization_declined" | "access_denied" => MicrosoftAuthError::AuthorizationDeclined,
+            "bad_verification_code" => MicrosoftAuthError::BadVerificationCode,
+            "expired_token" => MicrosoftAuthError::ExpiredToken,
+            "invalid_grant" => MicrosoftAuthError::InvalidGrant,
+            _other => MicrosoftAuthError::Others(
+                format!("Unknown error: {:?}", _other)
+            ),
         }
+    }
+
+    /// Read the OAuth2.0 error from the failed response of token endpoint.
+    ///
+    /// Return `Err` only when the response body can't be read.
+    async fn from_response(res:Response) -> Result<Self,MicrosoftAuthError>{
+        let code = res.json::<Value>()
+            .await?
+            .get("error")
+            .ok_or(MicrosoftAuthError::Others("Error while reading error field".to_string()))?
+            .as_str()
+            .ok_or(MicrosoftAuthError::Others("Error while convert error field to str".to_string()))?
+            .to_string();
 
+        Ok(Self::from_error_code(&code))
     }
 
 }
@@ -234,24 +284,132 @@ pub struct MicrosoftAuth {
     #[serde(deserialize_with = "sec_to_duration", serialize_with = "duration_to_sec")]
     #[dur]
     pub expires_in: Duration,
-    pub access_token: String,
-    pub refresh_token: String,
+    pub access_token: SecretString,
+    pub refresh_token: SecretString,
+}
+
+/// The token response of refresh token grant.
+///
+/// The server may not return a new refresh token or scope, in that case we keep the old one.
+#[derive(Debug, serde::Deserialize)]
+struct RefreshResponse {
+    token_type: String,
+    scope: Option<String>,
+    #[serde(deserialize_with = "sec_to_duration")]
+    expires_in: Duration,
+    access_token: SecretString,
+    refresh_token: Option<SecretString>,
+}
+
+/// Tell the caller whether the refresh token is rotated by [MicrosoftAuth::renew].
+///
+/// When the refresh token is rotated, the old one may be invalid soon,
+/// so you should persist the new one as soon as possible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenRotation {
+    /// The server issued a new refresh token.
+    Rotated,
+    /// The server kept the refresh token unchanged.
+    Unchanged,
+}
+
+impl MicrosoftAuth {
+
+    /// Renew the token with the refresh token, and replace access token, refresh token,
+    /// scope and expiry in place.
+    ///
+    /// Note this function won't update `created_at` of [ExpiringData], use [ExpiringData::refresh]
+    /// if you don't care about the rotation, or update `created_at` by yourself.
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    /// * `client_id`: The client id of your app, must be the same as the one used to login.
+    ///
+    /// # Returns
+    /// * Return [TokenRotation] to tell whether the refresh token is rotated.
+    /// * Return [MicrosoftAuthError::InvalidGrant] when the refresh token is expired, revoked or empty
+    ///   (like the account imported from the official launcher), you should ask the user to login again.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use reginleif::http::HttpContext;
+    /// use reginleif::auth::microsoft::{MicrosoftAuth, MicrosoftAuthError, TokenRotation};
+    /// use reginleif_utils::expiring_data::ExpiringData;
+    ///
+    /// async fn renew(msa:&mut ExpiringData<MicrosoftAuth>){
+    ///     match msa.data.renew(&HttpContext::default(),"your_client_id").await {
+    ///         Ok(rotation) => {
+    ///             msa.created_at = chrono::Local::now();
+    ///             if rotation == TokenRotation::Rotated {
+    ///                 // persist the new refresh token here.
+    ///             }
+    ///         }
+    ///         Err(MicrosoftAuthError::InvalidGrant) => {
+    ///             // ask the user to login again.
+    ///         }
+    ///         Err(e) => panic!("Error: {}",e),
+    ///     }
+    /// }
+    /// ```
+    pub async fn renew(&mut self, ctx:&HttpContext, client_id:&str) -> Result<TokenRotation,MicrosoftAuthError> {
+
+        // the server rejects it with invalid_request, which doesn't tell the user to login again.
+        if self.refresh_token.is_empty() {
+            return Err(MicrosoftAuthError::InvalidGrant);
+        }
+
+        let params = HashMap::from([
+            (String::from("client_id"), client_id.to_string()),
+            (String::from("grant_type"), String::from(REFRESH_GRANT_TYPE)),
+            (String::from("scope"), SCOPE.to_string()),
+            (String::from("refresh_token"), self.refresh_token.expose().to_string()),
+        ]);
+
+        let res = ctx.client.post(&ctx.endpoints.token)
+            .form(&params)
+            .send_limited(ctx)
+            .await?;
+
+        if !res.status().is_success() {
+            return Err(MicrosoftAuthError::from_response(res).await?);
+        }
+
+        let data = res.json::<RefreshResponse>().await?;
+
+        self.token_type = data.token_type;
+        self.expires_in = data.expires_in;
+        self.access_token = data.access_token;
+        if let Some(scope) = data.scope {
+            self.scope = scope;
+        }
+
+        match data.refresh_token {
+            Some(refresh_token) if refresh_token != self.refresh_token => {
+                self.refresh_token = refresh_token;
+                Ok(TokenRotation::Rotated)
+            }
+            _ => Ok(TokenRotation::Unchanged),
+        }
+    }
+
 }
 
 #[async_trait]
 impl Refreshable for MicrosoftAuth{
     
-    type Args = (Client,String);
+    type Args = (HttpContext,String);
     
     /// 
     /// 
     /// # Arguments 
     /// 
-    /// * `args`: &(Client,String)
-    ///     * Client: The reqwest client.
+    /// * `args`: &(HttpContext,String)
+    ///     * HttpContext: The HTTP context.
     ///     * String: The client id of your app.
     /// 
-    /// returns: anyhow::Result<()> 
+    /// returns: anyhow::Result<()>, the error can be downcast to [MicrosoftAuthError].
+    ///
+    /// If you want to know whether the refresh token is rotated, use [MicrosoftAuth::renew] instead.
     /// 
     /// # Examples 
     /// 
@@ -263,10 +421,11 @@ impl Refreshable for MicrosoftAuth{
     /// #[tokio::main]
     /// async fn main(){
     ///
+    ///  use reginleif::http::HttpContext;
     ///  use reginleif::auth::microsoft::DeviceCode;
-    ///  let client = reqwest::Client::new();    /// 
+    ///  let ctx = HttpContext::default();
     ///  let client_id = "your client id here";
-    ///  let res = DeviceCode::fetch(&client,client_id).await;
+    ///  let res = DeviceCode::fetch(&ctx,client_id).await;
     ///
     ///  let device_code = match res{
     ///     Ok(device_code) => { 
@@ -281,7 +440,7 @@ impl Refreshable for MicrosoftAuth{
     ///
     ///
     ///  let mut res = loop{
-    ///     let result = device_code.exchange(&client,client_id).await;
+    ///     let result = device_code.exchange(&ctx,client_id).await;
     ///     let res = match result{
     ///         Ok(res) => {res}
     ///         Err(e) => {
@@ -301,31 +460,15 @@ impl Refreshable for MicrosoftAuth{
     ///     let cloned = res.clone();
     ///         
     ///     tokio::time::sleep(Duration::from_secs(2)).await;
-    ///     res.refresh(&(client,client_id.to_string())).await.unwrap();
+    ///     res.refresh(&(ctx,client_id.to_string())).await.unwrap();
     ///
     ///         
     ///  }
     /// ```
-    async fn refresh(&mut self, args: &(Client,String)) -> anyhow::Result<()> {
-        
-        let (client,client_id) = args;
-
-        let params = HashMap::from([
-            (String::from("client_id"), client_id.to_string()),
-            (String::from("grant_type"), String::from(REFRESH_GRANT_TYPE)),
-            (String::from("scope"), SCOPE.to_string()),
-            (String::from("refresh_token"), self.refresh_token.to_string()),
-        ]);
+    async fn refresh(&mut self, args: &(HttpContext,String)) -> anyhow::Result<()> {
         
-        let mut data = client.post(TOKEN_URL)
-            .form(&params)
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
-            .await?;
-
-        self = &mut data;
+        let (ctx,client_id) = args;
+        self.renew(ctx,client_id).await?;
         Ok(())
     }
 }
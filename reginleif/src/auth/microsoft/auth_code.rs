@@ -0,0 +1,279 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use rand::RngCore;
+use reqwest::Url;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinSet;
+use reginleif_utils::expiring_data::ExpiringData;
+use crate::auth::constant::{AUTHORIZATION_CODE_GRANT_TYPE, SCOPE};
+use crate::auth::microsoft::{MicrosoftAuth, MicrosoftAuthError};
+use crate::auth::rate_limit::SendLimited;
+use crate::http::HttpContext;
+
+/// The max size of the redirect request head we will read.
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
+/// How long a connection can take to send the request head, so a silent client can't block the listener.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SUCCESS_PAGE: &str = "<html><body><h1>Login successful</h1><p>You can close this window and return to the launcher.</p></body></html>";
+const FAILURE_PAGE: &str = "<html><body><h1>Login failed</h1><p>Please return to the launcher and try again.</p></body></html>";
+
+/// Proof Key for Code Exchange
+///
+/// see [RFC 7636](https://datatracker.ietf.org/doc/html/rfc7636)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pkce {
+    /// The secret that is sent to the token endpoint.
+    pub verifier: String,
+    /// The `S256` challenge of verifier, which is sent to the authorize endpoint.
+    pub challenge: String,
+}
+
+impl Pkce {
+
+    /// Generate a new random verifier and its challenge.
+    pub fn generate() -> Self {
+        Self::from_verifier(&random_string())
+    }
+
+    /// Create the challenge from the given verifier.
+    pub fn from_verifier(verifier: &str) -> Self {
+        let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+        Self {
+            verifier: verifier.to_string(),
+            challenge,
+        }
+    }
+
+}
+
+/// 32 random bytes encoded in base64url, which is 43 characters.
+fn random_string() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Authorization Code Login
+///
+/// This struct drives the authorization code flow with PKCE, which is more friendly than
+/// device code flow for desktop app. It binds a temporary listener on `127.0.0.1` to capture
+/// the redirect, so `http://127.0.0.1` must be added to the redirect uri of your app registration
+/// (the port is ignored for the loopback address).
+///
+/// # Example
+/// ```no_run
+/// use reginleif::http::HttpContext;
+/// use reginleif::auth::microsoft::auth_code::AuthCodeLogin;
+///
+/// #[tokio::main]
+/// async fn main(){
+///     let login = AuthCodeLogin::bind(HttpContext::default(), "your_client_id").await.unwrap();
+///     println!("open in browser: {}",login.authorize_url());
+///
+///     let msa = login.wait().await.unwrap(); // plug it into Account.
+///     println!("{:?}",msa);
+/// }
+/// ```
+///
+/// The [wait](AuthCodeLogin::wait) function waits forever until the redirect comes,
+/// wrap it with [tokio::time::timeout] or drop it to cancel the login.
+#[derive(Debug)]
+pub struct AuthCodeLogin {
+    listener: TcpListener,
+    ctx: HttpContext,
+    authorize: Url,
+    client_id: String,
+    redirect_uri: String,
+    state: String,
+    pkce: Pkce,
+}
+
+impl AuthCodeLogin {
+
+    /// Bind the listener on a random port of `127.0.0.1`.
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    /// * `client_id`: The client id of your app.
+    pub async fn bind(ctx: HttpContext, client_id: &str) -> Result<Self, MicrosoftAuthError> {
+        Self::bind_on(ctx, client_id, 0).await
+    }
+
+    /// Bind the listener on the given port of `127.0.0.1`, `0` means a random port.
+    ///
+    /// Return [Others](MicrosoftAuthError::Others) if the authorize endpoint of `ctx` isn't a valid url.
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    /// * `client_id`: The client id of your app.
+    /// * `port`: The port to listen.
+    pub async fn bind_on(ctx: HttpContext, client_id: &str, port: u16) -> Result<Self, MicrosoftAuthError> {
+        let authorize = Url::parse(&ctx.endpoints.authorize)
+            .map_err(|e| MicrosoftAuthError::Others(format!("Invalid authorize endpoint: {}", e)))?;
+        let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], port))).await?;
+        // use the ip instead of `localhost`, which may be resolved to `::1` by the browser.
+        let redirect_uri = format!("http://127.0.0.1:{}", listener.local_addr()?.port());
+
+        Ok(Self {
+            listener,
+            ctx,
+            authorize,
+            client_id: client_id.to_string(),
+            redirect_uri,
+            state: random_string(),
+            pkce: Pkce::generate(),
+        })
+    }
+
+    /// The redirect uri the listener captures.
+    pub fn redirect_uri(&self) -> &str {
+        &self.redirect_uri
+    }
+
+    /// The url that the user should open in the browser.
+    pub fn authorize_url(&self) -> Url {
+        let mut url = self.authorize.clone();
+        url.query_pairs_mut()
+            .append_pair("client_id", &self.client_id)
+            .append_pair("response_type", "code")
+            .append_pair("response_mode", "query")
+            .append_pair("redirect_uri", &self.redirect_uri)
+            .append_pair("scope", SCOPE)
+            .append_pair("state", &self.state)
+            .append_pair("code_challenge", &self.pkce.challenge)
+            .append_pair("code_challenge_method", "S256")
+            .append_pair("prompt", "select_account");
+        url
+    }
+
+    /// Wait for the redirect and exchange the authorization code to a valid token.
+    ///
+    /// The requests without the `state` of this login (like the preconnect of the browser, a port probe
+    /// or a forged redirect) are answered with 400 and ignored, so only the real redirect ends the wait.
+    /// Every connection is handled in its own task, so an idle connection can't hold back the redirect.
+    pub async fn wait(self) -> Result<ExpiringData<MicrosoftAuth>, MicrosoftAuthError> {
+        let state: Arc<str> = Arc::from(self.state.as_str());
+        let mut connections = JoinSet::new(); // the pending connections are aborted when it's dropped.
+
+        let code = loop {
+            tokio::select! {
+                accepted = self.listener.accept() => {
+                    let (mut stream, _) = accepted?;
+                    let state = state.clone();
+                    connections.spawn(async move {
+                        tokio::time::timeout(READ_TIMEOUT, handle(&mut stream, &state)).await
+                    });
+                }
+                Some(joined) = connections.join_next(), if !connections.is_empty() => {
+                    match joined {
+                        Ok(Ok(Ok(Some(result)))) => break result?,
+                        _ => continue, // not our redirect, timed out or the connection is broken.
+                    }
+                }
+            }
+        };
+
+        self.exchange(&code).await
+    }
+
+    async fn exchange(&self, code: &str) -> Result<ExpiringData<MicrosoftAuth>, MicrosoftAuthError> {
+        let params = HashMap::from([
+            ("client_id", self.client_id.as_str()),
+            ("grant_type", AUTHORIZATION_CODE_GRANT_TYPE),
+            ("scope", SCOPE),
+            ("code", code),
+            ("redirect_uri", &self.redirect_uri),
+            ("code_verifier", &self.pkce.verifier),
+        ]);
+
+        let res = self.ctx.client.post(&self.ctx.endpoints.token)
+            .form(&params)
+            .send_limited(&self.ctx)
+            .await?;
+
+        if res.status().is_success() {
+            Ok(res.json::<MicrosoftAuth>().await?.into())
+        } else {
+            Err(MicrosoftAuthError::from_response(res).await?)
+        }
+    }
+
+}
+
+/// Answer the connection, return `None` if it's not the redirect of this login.
+async fn handle(stream: &mut TcpStream, state: &str) -> Result<Option<Result<String, MicrosoftAuthError>>, MicrosoftAuthError> {
+    let Some(query) = read_redirect(stream).await? else {
+        respond(stream, "404 Not Found", "").await?;
+        return Ok(None); // like favicon.ico, just ignore it.
+    };
+
+    if query.get("state").map(String::as_str) != Some(state) {
+        respond(stream, "400 Bad Request", "").await?;
+        return Ok(None);
+    }
+
+    // the browser may be closed already, the result is still valid.
+    if let Some(error) = query.get("error") {
+        let _ = respond(stream, "400 Bad Request", FAILURE_PAGE).await;
+        return Ok(Some(Err(MicrosoftAuthError::from_error_code(error))));
+    }
+    match query.get("code") {
+        Some(code) => {
+            let _ = respond(stream, "200 OK", SUCCESS_PAGE).await;
+            Ok(Some(Ok(code.clone())))
+        }
+        None => {
+            respond(stream, "400 Bad Request", "").await?;
+            Ok(None)
+        }
+    }
+}
+
+/// Read the request head and return the query of it.
+///
+/// Return `None` if the request is not sent to the redirect uri.
+async fn read_redirect(stream: &mut TcpStream) -> Result<Option<HashMap<String, String>>, MicrosoftAuthError> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !buf.windows(4).any(|x| x == b"\r\n\r\n") {
+        let size = stream.read(&mut chunk).await?;
+        if size == 0 || buf.len() + size > MAX_REQUEST_SIZE {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..size]);
+    }
+
+    let head = String::from_utf8_lossy(&buf);
+    let target = head.lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .unwrap_or_default();
+
+    let Ok(url) = Url::parse(&format!("http://localhost{}", target)) else {
+        return Ok(None);
+    };
+    if url.path() != "/" {
+        return Ok(None);
+    }
+
+    Ok(Some(url.query_pairs().into_owned().collect()))
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, body: &str) -> Result<(), MicrosoftAuthError> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
@@ -0,0 +1,213 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tokio_stream::Stream;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_util::sync::CancellationToken;
+use reginleif_utils::expiring_data::ExpiringData;
+use crate::auth::error::AuthError;
+use crate::auth::microsoft::{DeviceCode, MicrosoftAuth, MicrosoftAuthError};
+use crate::http::HttpContext;
+
+/// The extra seconds to add to the polling interval when the server responds `slow_down`.
+///
+/// see [RFC 8628](https://datatracker.ietf.org/doc/html/rfc8628#section-3.5)
+const SLOW_DOWN_STEP: Duration = Duration::from_secs(5);
+
+/// The state of a [DeviceCodeLogin].
+///
+/// [Success](DeviceCodeState::Success), [Declined](DeviceCodeState::Declined),
+/// [Expired](DeviceCodeState::Expired), [Cancelled](DeviceCodeState::Cancelled) and
+/// [Failed](DeviceCodeState::Failed) are terminal states, the stream ends after one of them.
+#[derive(Debug)]
+pub enum DeviceCodeState {
+    /// The device code is issued, you should show `user_code` and `verification_uri` to the user.
+    CodeIssued(DeviceCode),
+    /// Waiting for the user to finish the authorization.
+    Waiting {
+        /// The time before the next poll.
+        next_poll_in: Duration,
+        /// The time before the device code expires.
+        expires_in: Duration,
+    },
+    /// The user finished the authorization.
+    Success(ExpiringData<MicrosoftAuth>),
+    /// The user declined the authorization.
+    Declined,
+    /// The device code expired before the user finished the authorization.
+    Expired,
+    /// The login is cancelled by [DeviceCodeCancel::cancel].
+    Cancelled,
+    /// The login failed because of the error which is not related to the user.
+    Failed(AuthError),
+}
+
+/// The handle to cancel a running [DeviceCodeLogin].
+///
+/// This handle can be cloned and sent to other thread, like the UI thread.
+#[derive(Debug, Clone, Default)]
+pub struct DeviceCodeCancel(CancellationToken);
+
+impl DeviceCodeCancel {
+
+    /// Cancel the login, the stream will yield [DeviceCodeState::Cancelled] and end.
+    pub fn cancel(&self) {
+        self.0.cancel();
+    }
+
+    /// Check the login is cancelled or not.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.is_cancelled()
+    }
+
+}
+
+/// Device Code Login
+///
+/// This struct drives the whole device code flow for you: fetching the device code,
+/// polling the token with the interval the server asks (including `slow_down` backoff),
+/// and stopping when the device code expires.
+///
+/// The progress is exposed as a [Stream] of [DeviceCodeState], and the login can be cancelled by
+/// the [DeviceCodeCancel] handle. Dropping the stream will also stop the polling.
+///
+/// Note the login runs on a spawned tokio task, so it must be started inside a tokio runtime.
+///
+/// # Example
+/// ```no_run
+/// use reginleif::http::HttpContext;
+/// use tokio_stream::StreamExt;
+/// use reginleif::auth::microsoft::device_login::{DeviceCodeLogin, DeviceCodeState};
+///
+/// #[tokio::main]
+/// async fn main(){
+///     let login = DeviceCodeLogin::new(HttpContext::default(), "your_client_id");
+///     let mut stream = login.start();
+///     let cancel = stream.cancel_handle(); // give it to your UI.
+///
+///     while let Some(state) = stream.next().await {
+///         match state {
+///             DeviceCodeState::CodeIssued(code) => {
+///                 println!("auth url: {}",code.verification_uri);
+///                 println!("user code: {}",code.user_code);
+///             }
+///             DeviceCodeState::Success(msa) => println!("{:?}",msa),
+///             other => println!("{:?}",other),
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DeviceCodeLogin {
+    ctx: HttpContext,
+    client_id: String,
+}
+
+impl DeviceCodeLogin {
+
+    /// Create a new device code login.
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    /// * `client_id`: The client id of your app.
+    pub fn new(ctx: HttpContext, client_id: &str) -> Self {
+        Self {
+            ctx,
+            client_id: client_id.to_string(),
+        }
+    }
+
+    /// Start the login and return the stream of states.
+    pub fn start(self) -> DeviceCodeStream {
+        let (tx, rx) = mpsc::channel(8);
+        let cancel = DeviceCodeCancel::default();
+        let token = cancel.0.clone();
+
+        tokio::spawn(async move {
+            let state = tokio::select! {
+                _ = token.cancelled() => DeviceCodeState::Cancelled,
+                state = self.poll(&tx) => state,
+            };
+            let _ = tx.send(state).await; // the receiver may be dropped, nothing to do.
+        });
+
+        DeviceCodeStream {
+            inner: ReceiverStream::new(rx),
+            cancel,
+        }
+    }
+
+    /// Run the flow and return the terminal state.
+    ///
+    /// Returning `Cancelled` from here means the stream is dropped.
+    async fn poll(&self, tx: &mpsc::Sender<DeviceCodeState>) -> DeviceCodeState {
+        let device_code = match DeviceCode::fetch(&self.ctx, &self.client_id).await {
+            Ok(device_code) => device_code,
+            Err(e) => return DeviceCodeState::Failed(e),
+        };
+
+        let deadline = Instant::now() + device_code.expires_in;
+        let mut interval = device_code.interval;
+
+        if tx.send(DeviceCodeState::CodeIssued(device_code.clone())).await.is_err() {
+            return DeviceCodeState::Cancelled;
+        }
+
+        loop {
+            let expires_in = deadline.saturating_duration_since(Instant::now());
+            if expires_in.is_zero() {
+                return DeviceCodeState::Expired;
+            }
+
+            let next_poll_in = interval.min(expires_in);
+            let waiting = DeviceCodeState::Waiting { next_poll_in, expires_in };
+            if tx.send(waiting).await.is_err() {
+                return DeviceCodeState::Cancelled;
+            }
+            tokio::time::sleep(next_poll_in).await;
+
+            match device_code.exchange(&self.ctx, &self.client_id).await {
+                Ok(msa) => return DeviceCodeState::Success(msa),
+                Err(MicrosoftAuthError::AuthorizationPending) => {}
+                Err(MicrosoftAuthError::SlowDown) => interval += SLOW_DOWN_STEP,
+                Err(MicrosoftAuthError::AuthorizationDeclined) => return DeviceCodeState::Declined,
+                Err(MicrosoftAuthError::ExpiredToken) => return DeviceCodeState::Expired,
+                Err(e) => return DeviceCodeState::Failed(e.into()),
+            }
+        }
+    }
+
+}
+
+/// The stream of [DeviceCodeState] returned by [DeviceCodeLogin::start].
+#[derive(Debug)]
+pub struct DeviceCodeStream {
+    inner: ReceiverStream<DeviceCodeState>,
+    cancel: DeviceCodeCancel,
+}
+
+impl DeviceCodeStream {
+
+    /// Get the handle to cancel the login.
+    pub fn cancel_handle(&self) -> DeviceCodeCancel {
+        self.cancel.clone()
+    }
+
+}
+
+impl Stream for DeviceCodeStream {
+    type Item = DeviceCodeState;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.inner).poll_next(cx)
+    }
+}
+
+impl Drop for DeviceCodeStream {
+    /// Stop the polling, so no token is issued after the stream is dropped.
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
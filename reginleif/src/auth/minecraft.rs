@@ -1,31 +1,63 @@
 use std::time::Duration;
-use reqwest::Client;
+use reqwest::StatusCode;
+use reqwest::multipart::{Form, Part};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use reginleif_macro::{Expirable, NoRefresh};
-use crate::auth::constant::{MINECRAFT_LOGIN_WITH_XBOX, MINECRAFT_PROFILE};
+use chrono::{DateTime, Local};
+use reginleif_macro::NoRefresh;
+use reginleif_utils::expiring_data::Expirable;
+use crate::auth::error::AuthError;
 use crate::auth::xbox::XboxSecurityToken;
+use reginleif_utils::secret::SecretString;
 use reginleif_utils::serde_convert::{duration_to_sec, sec_to_duration};
+use crate::auth::rate_limit::SendLimited;
+use crate::http::HttpContext;
+
+/// The entitlements of the account, which tell whether the account owns the game.
+pub mod entitlement;
+/// The name of the profile, including availability check, profile creation and name change.
+pub mod name;
+/// The claims of the Minecraft access token.
+pub mod claims;
+/// The key pair of the player, which is used to sign the chat messages.
+pub mod certificate;
+/// The attributes of the player, including privileges, preferences and ban status.
+pub mod attribute;
+/// The skin texture, including caching and avatar rendering.
+pub mod texture;
+/// Look up other players by the name or the uuid.
+pub mod lookup;
 
 /// Minecraft Auth
 /// 
 /// This struct is used to authenticate the user with Minecraft Auth Server.
-#[derive(Serialize, Deserialize,Debug,Clone, Expirable, NoRefresh)]
+#[derive(Serialize, Deserialize,Debug,Clone, NoRefresh)]
 pub struct MinecraftAuth {
     
     /// UUID of MinecraftAuth, note this is **NOT** the UUID of the player.
     pub username: String,
     /// The access token you can use to access Minecraft.
-    pub access_token: String,
+    pub access_token: SecretString,
     
     /// The expires time of the access token.
     #[serde(deserialize_with = "sec_to_duration", serialize_with = "duration_to_sec")]
-    #[dur]
     pub expires_in: Duration,
     /// The token type of the access token. Always being "bearer"
     pub token_type: String,
 }
 
+/// The expiry is read from the `exp` claim of the access token if it can be decoded,
+/// otherwise `expires_in` is used.
+impl Expirable for MinecraftAuth {
+    fn get_duration(&self) -> Duration {
+        self.expires_in
+    }
+
+    fn expires_at(&self) -> Option<DateTime<Local>> {
+        self.claims().ok()?.expires_at()
+    }
+}
+
 impl MinecraftAuth{
     
     /// Fetch Minecraft Auth
@@ -33,25 +65,44 @@ impl MinecraftAuth{
     /// This function will fetch Minecraft Auth from the given Xbox Security Token.
     /// 
     /// # Arguments
-    /// * `client` - The reqwest client
+    /// * `ctx` - The HTTP context
     /// * `xbox_security_token` - The Xbox Security Token you get from [XboxSecurityToken::fetch](crate::auth::xbox::XboxSecurityToken::fetch)
-    pub async fn fetch(client: &Client, xbox_security_token: XboxSecurityToken) -> anyhow::Result<Self>{
+    pub async fn fetch(ctx: &HttpContext, xbox_security_token: XboxSecurityToken) -> Result<Self,AuthError>{
 
-        let res = client
-            .post(MINECRAFT_LOGIN_WITH_XBOX)
+        let res = ctx.client
+            .post(&ctx.endpoints.minecraft_login_with_xbox)
             .header("Content-Type", "application/json")
             .json(&json!({
-                "identityToken": format!("XBL3.0 x={};{}",xbox_security_token.uhs,xbox_security_token.token)
+                "identityToken": format!("XBL3.0 x={};{}",xbox_security_token.uhs,xbox_security_token.token.expose())
             }))
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
+            .send_limited(ctx)
             .await?;
 
-        Ok(res)
+        Ok(AuthError::check_minecraft(res).await?.json().await?)
     }
-    
+
+    /// Check whether the token is still accepted by Minecraft services.
+    ///
+    /// The token may be revoked before it expires (like the user changes the password),
+    /// so this sends a request to the profile endpoint, return `false` if the server responds 401.
+    /// The account without a profile is still valid.
+    ///
+    /// # Arguments
+    /// * `ctx` - The HTTP context
+    pub async fn validate(&self, ctx: &HttpContext) -> Result<bool,AuthError>{
+        let res = ctx.client
+            .get(&ctx.endpoints.minecraft_profile)
+            .bearer_auth(self.access_token.expose())
+            .send_limited(ctx)
+            .await?;
+
+        match res.status() {
+            StatusCode::UNAUTHORIZED => Ok(false),
+            StatusCode::NOT_FOUND => Ok(true),
+            _ => AuthError::check_minecraft(res).await.map(|_| true),
+        }
+    }
+
 }
 
 /// Minecraft Profile
@@ -75,23 +126,162 @@ impl Profile{
     /// Fetch Minecraft Profile
     /// 
     /// This function will fetch Minecraft Profile from the given Minecraft Auth.
-    /// If the user doesn't have game, the profile won't exist too!
+    /// If the user doesn't have game, the profile won't exist too, and [AuthError::ProfileNotFound] will be returned!
     /// 
     /// # Arguments
-    /// * `client` - The reqwest client
+    /// * `ctx` - The HTTP context
     /// * `microsoft_auth` - The Microsoft Auth you get from [MinecraftAuth::fetch](crate::auth::minecraft::MinecraftAuth::fetch)
-    pub async fn fetch(client: &Client, microsoft_auth: &MinecraftAuth) -> anyhow::Result<Profile>{
-        let res = client
-            .get(MINECRAFT_PROFILE)
-            .bearer_auth(&microsoft_auth.access_token)
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
+    pub async fn fetch(ctx: &HttpContext, microsoft_auth: &MinecraftAuth) -> Result<Profile,AuthError>{
+        let res = ctx.client
+            .get(&ctx.endpoints.minecraft_profile)
+            .bearer_auth(microsoft_auth.access_token.expose())
+            .send_limited(ctx)
+            .await?;
+
+        if res.status() == StatusCode::NOT_FOUND {
+            return Err(AuthError::ProfileNotFound);
+        }
+
+        Ok(AuthError::check_minecraft(res).await?.json().await?)
+    }
+
+    /// Upload a skin and make it active.
+    ///
+    /// The skin must be a 64x64 or 64x32 PNG image.
+    /// Return the updated profile.
+    ///
+    /// # Arguments
+    /// * `ctx` - The HTTP context
+    /// * `minecraft_auth` - The Minecraft Auth you get from [MinecraftAuth::fetch](crate::auth::minecraft::MinecraftAuth::fetch)
+    /// * `variant` - The model of the skin
+    /// * `png` - The bytes of the PNG image
+    pub async fn upload_skin(ctx: &HttpContext, minecraft_auth: &MinecraftAuth, variant: SkinVariant, png: Vec<u8>) -> Result<Profile,AuthError>{
+        let file = Part::bytes(png)
+            .file_name("skin.png")
+            .mime_str("image/png")?;
+        let form = Form::new()
+            .text("variant", variant.as_str())
+            .part("file", file);
+
+        let res = ctx.client
+            .post(format!("{}/skins",ctx.endpoints.minecraft_profile))
+            .bearer_auth(minecraft_auth.access_token.expose())
+            .multipart(form)
+            .send_limited(ctx)
+            .await?;
+
+        Ok(AuthError::check_minecraft(res).await?.json().await?)
+    }
+
+    /// Set the skin from a public url and make it active.
+    ///
+    /// Return the updated profile.
+    ///
+    /// # Arguments
+    /// * `ctx` - The HTTP context
+    /// * `minecraft_auth` - The Minecraft Auth you get from [MinecraftAuth::fetch](crate::auth::minecraft::MinecraftAuth::fetch)
+    /// * `variant` - The model of the skin
+    /// * `url` - The url of the skin image
+    pub async fn set_skin_url(ctx: &HttpContext, minecraft_auth: &MinecraftAuth, variant: SkinVariant, url: &str) -> Result<Profile,AuthError>{
+        let res = ctx.client
+            .post(format!("{}/skins",ctx.endpoints.minecraft_profile))
+            .bearer_auth(minecraft_auth.access_token.expose())
+            .json(&json!({
+                "variant": variant,
+                "url": url
+            }))
+            .send_limited(ctx)
             .await?;
 
-        Ok(res)
+        Ok(AuthError::check_minecraft(res).await?.json().await?)
     }
+
+    /// Reset the skin to the default one (Steve or Alex).
+    ///
+    /// Return the updated profile.
+    ///
+    /// # Arguments
+    /// * `ctx` - The HTTP context
+    /// * `minecraft_auth` - The Minecraft Auth you get from [MinecraftAuth::fetch](crate::auth::minecraft::MinecraftAuth::fetch)
+    pub async fn reset_skin(ctx: &HttpContext, minecraft_auth: &MinecraftAuth) -> Result<Profile,AuthError>{
+        let res = ctx.client
+            .delete(format!("{}/skins/active",ctx.endpoints.minecraft_profile))
+            .bearer_auth(minecraft_auth.access_token.expose())
+            .send_limited(ctx)
+            .await?;
+
+        Ok(AuthError::check_minecraft(res).await?.json().await?)
+    }
+
+    /// Show the cape, the cape must be one of [capes](Profile::capes).
+    ///
+    /// Return the updated profile.
+    ///
+    /// # Arguments
+    /// * `ctx` - The HTTP context
+    /// * `minecraft_auth` - The Minecraft Auth you get from [MinecraftAuth::fetch](crate::auth::minecraft::MinecraftAuth::fetch)
+    /// * `cape_id` - The id of the cape
+    pub async fn show_cape(ctx: &HttpContext, minecraft_auth: &MinecraftAuth, cape_id: &str) -> Result<Profile,AuthError>{
+        let res = ctx.client
+            .put(format!("{}/capes/active",ctx.endpoints.minecraft_profile))
+            .bearer_auth(minecraft_auth.access_token.expose())
+            .json(&json!({
+                "capeId": cape_id
+            }))
+            .send_limited(ctx)
+            .await?;
+
+        Ok(AuthError::check_minecraft(res).await?.json().await?)
+    }
+
+    /// Hide the active cape.
+    ///
+    /// Return the updated profile.
+    ///
+    /// # Arguments
+    /// * `ctx` - The HTTP context
+    /// * `minecraft_auth` - The Minecraft Auth you get from [MinecraftAuth::fetch](crate::auth::minecraft::MinecraftAuth::fetch)
+    pub async fn hide_cape(ctx: &HttpContext, minecraft_auth: &MinecraftAuth) -> Result<Profile,AuthError>{
+        let res = ctx.client
+            .delete(format!("{}/capes/active",ctx.endpoints.minecraft_profile))
+            .bearer_auth(minecraft_auth.access_token.expose())
+            .send_limited(ctx)
+            .await?;
+
+        Ok(AuthError::check_minecraft(res).await?.json().await?)
+    }
+
+    /// Get the active skin.
+    pub fn active_skin(&self) -> Option<&Skin>{
+        self.skins.iter().find(|x| x.state == "ACTIVE")
+    }
+
+    /// Get the active cape.
+    pub fn active_cape(&self) -> Option<&Caps>{
+        self.capes.iter().find(|x| x.state == "ACTIVE")
+    }
+}
+
+/// The model of the skin.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum SkinVariant {
+    /// The model with 4px arms (Steve).
+    Classic,
+    /// The model with 3px arms (Alex).
+    Slim,
+}
+
+impl SkinVariant {
+
+    /// The name used by Minecraft services.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SkinVariant::Classic => "classic",
+            SkinVariant::Slim => "slim",
+        }
+    }
+
 }
 
 
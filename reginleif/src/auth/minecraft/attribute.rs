@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::auth::error::AuthError;
+use crate::auth::minecraft::MinecraftAuth;
+use crate::auth::rate_limit::SendLimited;
+use crate::http::HttpContext;
+
+/// The scope of the ban which forbids the player to join multiplayer servers and Realms.
+pub const MULTIPLAYER_SCOPE: &str = "MULTIPLAYER";
+
+/// Player Attributes
+///
+/// This struct is used to store what the player is allowed to do, the preferences and the ban status.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerAttributes {
+    /// What the player is allowed to do.
+    #[serde(default)]
+    pub privileges: Privileges,
+    /// The profanity filter preferences.
+    #[serde(default)]
+    pub profanity_filter_preferences: ProfanityFilterPreferences,
+    /// The bans of the player.
+    #[serde(default)]
+    pub ban_status: BanStatus,
+}
+
+/// The privileges of the player, they are usually disabled by parental control (Xbox family settings).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Privileges {
+    /// Whether the player can chat online.
+    #[serde(default)]
+    pub online_chat: Privilege,
+    /// Whether the player can join multiplayer servers.
+    #[serde(default)]
+    pub multiplayer_server: Privilege,
+    /// Whether the player can join Realms.
+    #[serde(default)]
+    pub multiplayer_realms: Privilege,
+    /// Whether the game sends the required telemetry.
+    #[serde(default)]
+    pub telemetry: Privilege,
+    /// Whether the player can choose to send the optional telemetry.
+    #[serde(default)]
+    pub optional_telemetry: Privilege,
+}
+
+/// A privilege of the player.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct Privilege {
+    pub enabled: bool,
+}
+
+/// The profanity filter preferences of the player.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfanityFilterPreferences {
+    /// Whether the profanity filter of Realms chat is on.
+    pub profanity_filter_on: bool,
+}
+
+/// The ban status of the player.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BanStatus {
+    /// The bans keyed by the scope, like [MULTIPLAYER_SCOPE].
+    #[serde(default)]
+    pub banned_scopes: HashMap<String, Ban>,
+}
+
+impl BanStatus {
+
+    /// Get the ban from multiplayer, return `None` if the player isn't banned or the ban is over.
+    pub fn multiplayer(&self) -> Option<&Ban> {
+        self.banned_scopes.get(MULTIPLAYER_SCOPE).filter(|x| x.is_active())
+    }
+
+}
+
+/// A ban of the player.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Ban {
+    /// The id of the ban, the player needs it to appeal.
+    pub ban_id: String,
+    /// When the ban is over, in unix timestamp (milliseconds), `None` means forever.
+    pub expires: Option<i64>,
+    /// The reason of the ban, like `hate_terrorism_notorious_figure`.
+    pub reason: String,
+    /// The message shown to the player.
+    pub reason_message: Option<String>,
+}
+
+impl Ban {
+
+    /// Get the time the ban is over, return `None` if it's forever.
+    pub fn expires_at(&self) -> Option<DateTime<Local>> {
+        Local.timestamp_millis_opt(self.expires?).single()
+    }
+
+    /// Whether the ban is still active.
+    pub fn is_active(&self) -> bool {
+        match self.expires_at() {
+            Some(expires_at) => Local::now() < expires_at,
+            None => true,
+        }
+    }
+
+}
+
+impl PlayerAttributes {
+
+    /// Fetch the attributes of the player.
+    ///
+    /// # Arguments
+    /// * `ctx` - The HTTP context
+    /// * `minecraft_auth` - The Minecraft Auth you get from [MinecraftAuth::fetch](crate::auth::minecraft::MinecraftAuth::fetch)
+    pub async fn fetch(ctx: &HttpContext, minecraft_auth: &MinecraftAuth) -> Result<Self, AuthError> {
+        let res = ctx.client
+            .get(format!("{}/attributes", ctx.endpoints.minecraft_player))
+            .bearer_auth(minecraft_auth.access_token.expose())
+            .send_limited(ctx)
+            .await?;
+
+        Ok(AuthError::check_minecraft(res).await?.json().await?)
+    }
+
+    /// Turn the profanity filter on or off, return the updated attributes.
+    ///
+    /// # Arguments
+    /// * `ctx` - The HTTP context
+    /// * `minecraft_auth` - The Minecraft Auth you get from [MinecraftAuth::fetch](crate::auth::minecraft::MinecraftAuth::fetch)
+    /// * `on` - Whether the profanity filter is on
+    pub async fn set_profanity_filter(ctx: &HttpContext, minecraft_auth: &MinecraftAuth, on: bool) -> Result<Self, AuthError> {
+        let res = ctx.client
+            .post(format!("{}/attributes", ctx.endpoints.minecraft_player))
+            .bearer_auth(minecraft_auth.access_token.expose())
+            .json(&json!({
+                "profanityFilterPreferences": {
+                    "profanityFilterOn": on
+                }
+            }))
+            .send_limited(ctx)
+            .await?;
+
+        Ok(AuthError::check_minecraft(res).await?.json().await?)
+    }
+
+    /// Whether the player can join multiplayer servers, it's `false` if the privilege is disabled or the player is banned.
+    pub fn can_join_servers(&self) -> bool {
+        self.privileges.multiplayer_server.enabled && self.ban_status.multiplayer().is_none()
+    }
+
+    /// Whether the player can join Realms, it's `false` if the privilege is disabled or the player is banned.
+    pub fn can_join_realms(&self) -> bool {
+        self.privileges.multiplayer_realms.enabled && self.ban_status.multiplayer().is_none()
+    }
+
+}
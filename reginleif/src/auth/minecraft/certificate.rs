@@ -0,0 +1,126 @@
+use std::time::Duration;
+use async_trait::async_trait;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use reginleif_utils::expiring_data::{Expirable, ExpiringData, Refreshable};
+use reginleif_utils::secret::SecretString;
+use reginleif_utils::serde_convert::{local_to_string, string_to_local};
+use crate::auth::error::AuthError;
+use crate::auth::minecraft::MinecraftAuth;
+use crate::auth::rate_limit::SendLimited;
+use crate::http::HttpContext;
+
+/// The RSA key pair of the player in PEM format.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyPair {
+    /// The private key, it's used to sign the chat messages.
+    pub private_key: SecretString,
+    /// The public key, it's sent to the server with the signatures below.
+    pub public_key: String,
+}
+
+/// Player Certificates
+///
+/// The key pair which is used to sign the chat messages (since 1.19), and the signatures of the public key
+/// signed by Mojang.
+///
+/// The certificates should be refreshed after [refreshed_after](PlayerCertificates::refreshed_after),
+/// and can't be used after [expires_at](PlayerCertificates::expires_at).
+/// It implements [Expirable] with `refreshed_after` as the expiry time, so you can cache it with [ExpiringData]
+/// and refresh it by [ExpiringData::try_ref].
+///
+/// # Example
+/// ```no_run
+/// use reginleif::http::HttpContext;
+/// use reginleif::auth::minecraft::MinecraftAuth;
+/// use reginleif::auth::minecraft::certificate::PlayerCertificates;
+///
+/// async fn example(minecraft_auth:MinecraftAuth){
+///     let ctx = HttpContext::default();
+///
+///     let mut certificates = PlayerCertificates::fetch_expiring(&ctx,&minecraft_auth).await.unwrap();
+///     // later, the certificates will be refreshed if needed.
+///     let certificates = certificates.try_ref(&(ctx,minecraft_auth)).await.unwrap();
+///     println!("{}",certificates.key_pair.public_key);
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerCertificates {
+    /// The key pair of the player.
+    pub key_pair: KeyPair,
+    /// The signature of the public key, used before 1.19.1.
+    pub public_key_signature: String,
+    /// The signature of the public key with the uuid of the player, used since 1.19.1.
+    pub public_key_signature_v2: String,
+    /// The time the certificates can't be used anymore.
+    #[serde(deserialize_with = "string_to_local", serialize_with = "local_to_string")]
+    pub expires_at: DateTime<Local>,
+    /// The time the certificates should be refreshed.
+    #[serde(deserialize_with = "string_to_local", serialize_with = "local_to_string")]
+    pub refreshed_after: DateTime<Local>,
+}
+
+impl PlayerCertificates {
+
+    /// Fetch the certificates, Mojang returns the same key pair until it should be refreshed.
+    ///
+    /// # Arguments
+    /// * `ctx` - The HTTP context
+    /// * `minecraft_auth` - The Minecraft Auth you get from [MinecraftAuth::fetch](crate::auth::minecraft::MinecraftAuth::fetch)
+    pub async fn fetch(ctx: &HttpContext, minecraft_auth: &MinecraftAuth) -> Result<Self, AuthError> {
+        let res = ctx.client
+            .post(format!("{}/certificates", ctx.endpoints.minecraft_player))
+            .bearer_auth(minecraft_auth.access_token.expose())
+            .send_limited(ctx)
+            .await?;
+
+        Ok(AuthError::check_minecraft(res).await?.json().await?)
+    }
+
+    /// Like [fetch](PlayerCertificates::fetch), but wrap it in [ExpiringData] for caching.
+    ///
+    /// # Arguments
+    /// * `ctx` - The HTTP context
+    /// * `minecraft_auth` - The Minecraft Auth you get from [MinecraftAuth::fetch](crate::auth::minecraft::MinecraftAuth::fetch)
+    pub async fn fetch_expiring(ctx: &HttpContext, minecraft_auth: &MinecraftAuth) -> Result<ExpiringData<Self>, AuthError> {
+        Ok(Self::fetch(ctx, minecraft_auth).await?.into())
+    }
+
+    /// Whether the certificates can still be used, it may be `true` even if they should be refreshed.
+    pub fn is_valid(&self) -> bool {
+        Local::now() < self.expires_at
+    }
+
+}
+
+/// The certificates expire at [refreshed_after](PlayerCertificates::refreshed_after).
+impl Expirable for PlayerCertificates {
+
+    /// The time from now to `refreshed_after`, it's only used when `expires_at` is unknown, which never happens.
+    fn get_duration(&self) -> Duration {
+        (self.refreshed_after - Local::now()).to_std().unwrap_or_default()
+    }
+
+    fn expires_at(&self) -> Option<DateTime<Local>> {
+        Some(self.refreshed_after)
+    }
+
+}
+
+#[async_trait]
+impl Refreshable for PlayerCertificates {
+
+    /// HTTP context and Minecraft Auth
+    type Args = (HttpContext, MinecraftAuth);
+
+    /// Fetch the certificates again.
+    ///
+    /// The error can be downcast to [AuthError].
+    async fn refresh(&mut self, (ctx, minecraft_auth): &Self::Args) -> anyhow::Result<()> {
+        *self = Self::fetch(ctx, minecraft_auth).await?;
+        Ok(())
+    }
+
+}
@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use crate::auth::error::AuthError;
+use crate::auth::minecraft::MinecraftAuth;
+
+/// The claims of the Minecraft access token (JWT).
+///
+/// The signature is **NOT** checked, don't use it to trust the token from others.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MinecraftTokenClaims {
+    /// The Xbox user id.
+    pub xuid: Option<String>,
+    /// The id of the Minecraft account, which is the same as [MinecraftAuth::username].
+    pub sub: String,
+    /// The profiles bound to the token, the key `mc` is the uuid of the player.
+    #[serde(default)]
+    pub profiles: HashMap<String, String>,
+    /// The platform the token is issued for, like `PC_LAUNCHER`.
+    pub platform: Option<String>,
+    /// Issued at, in unix timestamp (seconds).
+    pub iat: i64,
+    /// Expiration time, in unix timestamp (seconds).
+    pub exp: i64,
+    /// Not before, in unix timestamp (seconds).
+    pub nbf: Option<i64>,
+}
+
+impl MinecraftTokenClaims {
+
+    /// Decode the claims from the JWT without checking the signature.
+    ///
+    /// # Arguments
+    /// * `token`: The JWT.
+    pub fn decode(token: &str) -> Result<Self, AuthError> {
+        let payload = token.split('.')
+            .nth(1)
+            .ok_or(AuthError::InvalidResponse("The access token is not a JWT".to_string()))?;
+        let payload = URL_SAFE_NO_PAD
+            .decode(payload.trim_end_matches('='))
+            .map_err(|e| AuthError::InvalidResponse(format!("Error while decoding JWT payload: {}", e)))?;
+
+        serde_json::from_slice(&payload)
+            .map_err(|e| AuthError::InvalidResponse(format!("Error while parsing JWT claims: {}", e)))
+    }
+
+    /// Get the uuid of the player bound to the token.
+    pub fn profile_id(&self) -> Option<&str> {
+        self.profiles.get("mc").map(|x| x.as_str())
+    }
+
+    /// Get the time the token is issued.
+    pub fn issued_at(&self) -> Option<DateTime<Local>> {
+        Local.timestamp_opt(self.iat, 0).single()
+    }
+
+    /// Get the time the token expires.
+    pub fn expires_at(&self) -> Option<DateTime<Local>> {
+        Local.timestamp_opt(self.exp, 0).single()
+    }
+
+}
+
+impl MinecraftAuth {
+
+    /// Decode the claims of the access token, see [MinecraftTokenClaims].
+    pub fn claims(&self) -> Result<MinecraftTokenClaims, AuthError> {
+        MinecraftTokenClaims::decode(self.access_token.expose())
+    }
+
+}
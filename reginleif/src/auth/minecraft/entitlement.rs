@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use crate::auth::error::AuthError;
+use crate::auth::minecraft::MinecraftAuth;
+use crate::auth::rate_limit::SendLimited;
+use crate::http::HttpContext;
+
+/// The entitlement names which mean the account can play Java Edition.
+const JAVA_EDITION: &[&str] = &["product_minecraft", "game_minecraft"];
+
+/// Minecraft Entitlements
+///
+/// This struct is used to store the entitlements (the products the account owns) from Minecraft services.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Entitlements {
+    /// The entitlements the account has.
+    #[serde(default)]
+    pub items: Vec<Entitlement>,
+    /// The JWT signed by Mojang, which contains the same data as `items`.
+    pub signature: String,
+    /// The id of the key used to sign the `signature`.
+    pub key_id: String,
+    /// The request id we sent, only exists in the response of license endpoint.
+    pub request_id: Option<String>,
+}
+
+/// Minecraft Entitlement
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Entitlement {
+    /// The name of the entitlement, like `product_minecraft` or `game_minecraft`.
+    pub name: String,
+    /// Where the entitlement comes from, like `PURCHASE`, `MC_PURCHASE` or `GAMEPASS`.
+    ///
+    /// Only exists in the response of license endpoint.
+    pub source: Option<String>,
+    /// The JWT signed by Mojang of this entitlement.
+    pub signature: Option<String>,
+}
+
+/// How the account owns Java Edition.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+pub enum GameOwnership {
+    /// The account bought the game.
+    Purchased,
+    /// The account can play the game through Xbox Game Pass.
+    GamePass,
+    /// The account doesn't own the game, you can fall back to demo mode or show a purchase hint.
+    NotOwned,
+    /// The ownership is not checked yet, or the entitlements can't be fetched.
+    #[default]
+    Unknown,
+}
+
+impl GameOwnership {
+
+    /// Whether the account can play the full game.
+    pub fn owns_game(&self) -> bool {
+        matches!(self, GameOwnership::Purchased | GameOwnership::GamePass)
+    }
+
+}
+
+impl Entitlements {
+
+    /// Fetch the entitlements of the account from Minecraft store.
+    ///
+    /// Note the accounts which play through Xbox Game Pass may have no entitlement here,
+    /// use [fetch_license](Entitlements::fetch_license) if you want to check the ownership.
+    ///
+    /// # Arguments
+    /// * `ctx` - The HTTP context
+    /// * `minecraft_auth` - The Minecraft Auth you get from [MinecraftAuth::fetch](crate::auth::minecraft::MinecraftAuth::fetch)
+    pub async fn fetch_store(ctx: &HttpContext, minecraft_auth: &MinecraftAuth) -> Result<Self, AuthError> {
+        let res = ctx.client
+            .get(format!("{}/mcstore", ctx.endpoints.minecraft_entitlements))
+            .bearer_auth(minecraft_auth.access_token.expose())
+            .send_limited(ctx)
+            .await?;
+
+        Ok(AuthError::check_minecraft(res).await?.json().await?)
+    }
+
+    /// Fetch the licenses of the account, including the ones from Xbox Game Pass.
+    ///
+    /// # Arguments
+    /// * `ctx` - The HTTP context
+    /// * `minecraft_auth` - The Minecraft Auth you get from [MinecraftAuth::fetch](crate::auth::minecraft::MinecraftAuth::fetch)
+    pub async fn fetch_license(ctx: &HttpContext, minecraft_auth: &MinecraftAuth) -> Result<Self, AuthError> {
+        let res = ctx.client
+            .get(format!("{}/license", ctx.endpoints.minecraft_entitlements))
+            .query(&[("requestId", uuid::Uuid::new_v4().to_string())])
+            .bearer_auth(minecraft_auth.access_token.expose())
+            .send_limited(ctx)
+            .await?;
+
+        Ok(AuthError::check_minecraft(res).await?.json().await?)
+    }
+
+    /// Iterate the entitlements of Java Edition.
+    fn java_edition(&self) -> impl Iterator<Item = &Entitlement> {
+        self.items.iter().filter(|x| JAVA_EDITION.contains(&x.name.as_str()))
+    }
+
+    /// Whether the account can play Java Edition.
+    pub fn owns_java(&self) -> bool {
+        self.java_edition().next().is_some()
+    }
+
+    /// How the account owns Java Edition.
+    ///
+    /// If all the entitlements of Java Edition come from Xbox Game Pass, return [GameOwnership::GamePass].
+    pub fn ownership(&self) -> GameOwnership {
+        if !self.owns_java() {
+            return GameOwnership::NotOwned;
+        }
+
+        let game_pass_only = self.java_edition()
+            .all(|x| x.source.as_deref() == Some("GAMEPASS"));
+
+        if game_pass_only {
+            GameOwnership::GamePass
+        } else {
+            GameOwnership::Purchased
+        }
+    }
+
+}
@@ -0,0 +1,306 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use crate::auth::error::AuthError;
+use crate::auth::minecraft::SkinVariant;
+use crate::auth::minecraft::name::is_valid_name;
+use crate::auth::rate_limit::SendLimited;
+use crate::http::HttpContext;
+
+/// The maximum number of names in one bulk lookup request.
+const BULK_LIMIT: usize = 10;
+
+/// The uuid and the name of a player.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct PlayerId {
+    /// The uuid of the player without dashes.
+    pub id: String,
+    /// The name of the player with the correct case.
+    pub name: String,
+}
+
+/// The public profile of a player from the session server.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerProfile {
+    /// The uuid of the player without dashes.
+    pub id: String,
+    /// The name of the player.
+    pub name: String,
+    /// The properties of the profile, usually only `textures`.
+    #[serde(default)]
+    pub properties: Vec<ProfileProperty>,
+    /// The actions the player must take, like `FORCED_NAME_CHANGE`.
+    #[serde(default)]
+    pub profile_actions: Vec<String>,
+}
+
+/// A property of [PlayerProfile].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProfileProperty {
+    pub name: String,
+    /// The base64 encoded value.
+    pub value: String,
+    /// The base64 encoded signature of the value signed by Mojang.
+    pub signature: Option<String>,
+}
+
+/// The decoded `textures` property of [PlayerProfile].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileTextures {
+    /// When the property is generated, in unix timestamp (milliseconds).
+    pub timestamp: i64,
+    pub profile_id: String,
+    pub profile_name: String,
+    #[serde(default)]
+    pub signature_required: bool,
+    pub textures: Textures,
+}
+
+/// The skin and the cape of [ProfileTextures], `None` means the player uses the default one.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct Textures {
+    #[serde(rename = "SKIN")]
+    pub skin: Option<Texture>,
+    #[serde(rename = "CAPE")]
+    pub cape: Option<Texture>,
+}
+
+/// A texture on textures.minecraft.net.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Texture {
+    pub url: String,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+impl Texture {
+
+    /// The model of the skin, it's classic unless the metadata says `slim`.
+    pub fn variant(&self) -> SkinVariant {
+        match self.metadata.get("model").map(String::as_str) {
+            Some("slim") => SkinVariant::Slim,
+            _ => SkinVariant::Classic,
+        }
+    }
+
+}
+
+impl PlayerProfile {
+
+    /// Decode the `textures` property, return `None` if the profile doesn't have it.
+    pub fn textures(&self) -> Result<Option<ProfileTextures>, AuthError> {
+        let Some(property) = self.properties.iter().find(|x| x.name == "textures") else {
+            return Ok(None);
+        };
+
+        let json = STANDARD.decode(&property.value)
+            .map_err(|e| AuthError::InvalidResponse(format!("Error while decoding textures: {}", e)))?;
+        serde_json::from_slice(&json)
+            .map(Some)
+            .map_err(|e| AuthError::InvalidResponse(format!("Error while parsing textures: {}", e)))
+    }
+
+}
+
+/// The in-memory cache keyed by the lowercase name or uuid, with the time the result is cached.
+type Cache<T> = Mutex<HashMap<String, (Instant, T)>>;
+
+fn get_cached<T: Clone>(cache: &Cache<T>, key: &str, ttl: Duration) -> Option<T> {
+    let cache = cache.lock().unwrap();
+    cache.get(key).filter(|(cached_at, _)| cached_at.elapsed() < ttl).map(|(_, value)| value.clone())
+}
+
+fn put_cached<T>(cache: &Cache<T>, key: String, value: T, ttl: Duration) {
+    let mut cache = cache.lock().unwrap();
+    cache.retain(|_, (cached_at, _)| cached_at.elapsed() < ttl);
+    cache.insert(key, (Instant::now(), value));
+}
+
+/// Remove the dashes and lowercase the uuid.
+fn normalize_uuid(uuid: &str) -> String {
+    uuid.replace('-', "").to_lowercase()
+}
+
+/// Whether the normalized uuid is 32 hex digits.
+fn is_valid_uuid(uuid: &str) -> bool {
+    uuid.len() == 32 && uuid.bytes().all(|x| x.is_ascii_hexdigit())
+}
+
+/// Profile Lookup
+///
+/// This struct looks up other players by the name or the uuid, which doesn't need any token.
+///
+/// Mojang limits the rate of these APIs, so the results (including "not found") are cached in memory
+/// for [ttl](ProfileLookup::with_ttl), share one instance in your app to make the cache work.
+///
+/// # Example
+/// ```no_run
+/// use reginleif::http::HttpContext;
+/// use reginleif::auth::minecraft::lookup::ProfileLookup;
+///
+/// async fn example(){
+///     let lookup = ProfileLookup::new(HttpContext::default());
+///
+///     let notch = lookup.uuid_of("Notch").await.unwrap().unwrap();
+///     let profile = lookup.profile(&notch.id).await.unwrap().unwrap();
+///     let textures = profile.textures().unwrap().unwrap();
+///     println!("{:?}",textures.textures.skin);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ProfileLookup {
+    ctx: HttpContext,
+    ttl: Duration,
+    names: Cache<Option<PlayerId>>,
+    profiles: Cache<Option<PlayerProfile>>,
+}
+
+impl ProfileLookup {
+
+    /// Create the lookup client, the results are cached for 5 minutes by default.
+    ///
+    /// # Arguments
+    /// * `ctx` - The HTTP context, the endpoints of it are used to look up
+    pub fn new(ctx: HttpContext) -> Self {
+        Self {
+            ctx,
+            ttl: Duration::from_secs(5 * 60),
+            names: Default::default(),
+            profiles: Default::default(),
+        }
+    }
+
+    /// Set how long the results are cached.
+    ///
+    /// # Arguments
+    /// * `ttl` - The time to live of the cached results
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Remove all the cached results.
+    pub fn clear_cache(&self) {
+        self.names.lock().unwrap().clear();
+        self.profiles.lock().unwrap().clear();
+    }
+
+    /// Look up the uuid by the name, return `None` if the player doesn't exist.
+    ///
+    /// The name which isn't a valid player name (see [is_valid_name]) returns `None` without sending the request.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the player, case-insensitive
+    pub async fn uuid_of(&self, name: &str) -> Result<Option<PlayerId>, AuthError> {
+        if !is_valid_name(name) {
+            return Ok(None);
+        }
+        let key = name.to_lowercase();
+        if let Some(cached) = get_cached(&self.names, &key, self.ttl) {
+            return Ok(cached);
+        }
+
+        let res = self.ctx.client
+            .get(format!("{}/{}", self.ctx.endpoints.mojang_name_lookup, name))
+            .send_limited(&self.ctx)
+            .await?;
+
+        let id = match res.status() {
+            StatusCode::NO_CONTENT | StatusCode::NOT_FOUND => None,
+            _ => Some(AuthError::check_public(res).await?.json::<PlayerId>().await?),
+        };
+
+        put_cached(&self.names, key, id.clone(), self.ttl);
+        Ok(id)
+    }
+
+    /// Look up the uuids by the names, the players that don't exist are left out.
+    ///
+    /// The names are deduplicated case-insensitively, and the invalid names (see [is_valid_name]) are left out
+    /// without sending the request. The names not in the cache are fetched 10 at a time.
+    ///
+    /// # Arguments
+    /// * `names` - The names of the players, case-insensitive
+    pub async fn uuids_of(&self, names: &[&str]) -> Result<Vec<PlayerId>, AuthError> {
+        let mut found = vec![];
+        let mut missing = vec![];
+        let mut seen = HashSet::new();
+
+        for name in names {
+            let key = name.to_lowercase();
+            if !is_valid_name(name) || !seen.insert(key.clone()) {
+                continue;
+            }
+            match get_cached(&self.names, &key, self.ttl) {
+                Some(cached) => found.extend(cached),
+                None => missing.push(*name),
+            }
+        }
+
+        for chunk in missing.chunks(BULK_LIMIT) {
+            let res = self.ctx.client
+                .post(&self.ctx.endpoints.minecraft_bulk_lookup)
+                .json(chunk)
+                .send_limited(&self.ctx)
+                .await?;
+
+            let ids: Vec<PlayerId> = AuthError::check_public(res).await?.json().await?;
+
+            for name in chunk {
+                let id = ids.iter().find(|x| x.name.eq_ignore_ascii_case(name)).cloned();
+                put_cached(&self.names, name.to_lowercase(), id, self.ttl);
+            }
+            found.extend(ids);
+        }
+
+        Ok(found)
+    }
+
+    /// Fetch the public profile with the signed textures by the uuid, return `None` if the player doesn't exist.
+    ///
+    /// The string which isn't a uuid returns `None` without sending the request.
+    ///
+    /// # Arguments
+    /// * `uuid` - The uuid of the player, with or without dashes
+    pub async fn profile(&self, uuid: &str) -> Result<Option<PlayerProfile>, AuthError> {
+        let key = normalize_uuid(uuid);
+        if !is_valid_uuid(&key) {
+            return Ok(None);
+        }
+        if let Some(cached) = get_cached(&self.profiles, &key, self.ttl) {
+            return Ok(cached);
+        }
+
+        let res = self.ctx.client
+            .get(format!("{}/{}", self.ctx.endpoints.session_profile, key))
+            .query(&[("unsigned", "false")])
+            .send_limited(&self.ctx)
+            .await?;
+
+        let profile = match res.status() {
+            StatusCode::NO_CONTENT | StatusCode::NOT_FOUND => None,
+            _ => Some(AuthError::check_public(res).await?.json::<PlayerProfile>().await?),
+        };
+
+        put_cached(&self.profiles, key, profile.clone(), self.ttl);
+        Ok(profile)
+    }
+
+    /// Look up the uuid by the name, then fetch the profile.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the player, case-insensitive
+    pub async fn profile_of(&self, name: &str) -> Result<Option<PlayerProfile>, AuthError> {
+        match self.uuid_of(name).await? {
+            Some(id) => self.profile(&id.id).await,
+            None => Ok(None),
+        }
+    }
+
+}
@@ -0,0 +1,189 @@
+use chrono::{DateTime, Local};
+use reqwest::{Response, StatusCode};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::json;
+use reginleif_utils::serde_convert::string_to_local;
+use crate::auth::error::{AuthError, MinecraftServicesError};
+use crate::auth::minecraft::{MinecraftAuth, Profile};
+use crate::auth::rate_limit::SendLimited;
+use crate::http::HttpContext;
+
+/// Whether a name can be used as the profile name.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum NameAvailability {
+    /// The name can be used.
+    Available,
+    /// The name is already used by another player.
+    Duplicate,
+    /// The name is blocked or doesn't match the rules (3-16 characters of `a-zA-Z0-9_`).
+    NotAllowed,
+}
+
+impl NameAvailability {
+
+    /// Whether the name can be used.
+    pub fn is_available(&self) -> bool {
+        matches!(self, NameAvailability::Available)
+    }
+
+}
+
+/// Check whether the name matches the rules of the profile name (3-16 characters of `a-zA-Z0-9_`).
+///
+/// The name which doesn't match the rules can't be used by any player,
+/// so it's checked before being put into the url.
+///
+/// # Arguments
+/// * `name` - The name to check
+pub fn is_valid_name(name: &str) -> bool {
+    (3..=16).contains(&name.len()) && name.bytes().all(|x| x.is_ascii_alphanumeric() || x == b'_')
+}
+
+/// The information about whether the profile can change its name.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NameChangeInfo {
+    /// When the name was changed last time, `None` if the name has never been changed.
+    #[serde(default, deserialize_with = "optional_local")]
+    pub changed_at: Option<DateTime<Local>>,
+    /// When the profile was created.
+    #[serde(deserialize_with = "string_to_local")]
+    pub created_at: DateTime<Local>,
+    /// Whether the profile can change its name now.
+    pub name_change_allowed: bool,
+}
+
+/// The error body when the name can't be used.
+#[derive(Deserialize)]
+struct NameError {
+    details: NameErrorDetails,
+}
+
+#[derive(Deserialize)]
+struct NameErrorDetails {
+    status: NameAvailability,
+}
+
+fn optional_local<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<DateTime<Local>>, D::Error> {
+    match Option::<String>::deserialize(deserializer)? {
+        Some(str) => DateTime::parse_from_rfc3339(&str)
+            .map(|x| Some(x.with_timezone(&Local)))
+            .map_err(de::Error::custom),
+        None => Ok(None),
+    }
+}
+
+/// Parse the profile from the response of create or change name.
+///
+/// If the name can't be used, return [MinecraftServicesError::NameUnavailable].
+async fn name_response(res: Response) -> Result<Profile, AuthError> {
+    match res.status() {
+        StatusCode::BAD_REQUEST | StatusCode::FORBIDDEN | StatusCode::CONFLICT => {
+            let url = res.url().to_string();
+            let status = res.status();
+            let body = res.text().await?;
+
+            if let Ok(err) = serde_json::from_str::<NameError>(&body) {
+                return Err(MinecraftServicesError::NameUnavailable(err.details.status).into());
+            }
+            if status == StatusCode::FORBIDDEN {
+                return Err(MinecraftServicesError::Forbidden(body).into());
+            }
+            Err(AuthError::Http { url, status, body })
+        }
+        _ => Ok(AuthError::check_minecraft(res).await?.json().await?),
+    }
+}
+
+impl Profile {
+
+    /// Check whether the name can be used, it works with or without profile.
+    ///
+    /// The name which doesn't match the rules (see [is_valid_name]) is [NameAvailability::NotAllowed]
+    /// without sending the request.
+    ///
+    /// # Arguments
+    /// * `ctx` - The HTTP context
+    /// * `minecraft_auth` - The Minecraft Auth you get from [MinecraftAuth::fetch](crate::auth::minecraft::MinecraftAuth::fetch)
+    /// * `name` - The name to check
+    pub async fn check_name(ctx: &HttpContext, minecraft_auth: &MinecraftAuth, name: &str) -> Result<NameAvailability, AuthError> {
+        #[derive(Deserialize)]
+        struct Response {
+            status: NameAvailability,
+        }
+
+        if !is_valid_name(name) {
+            return Ok(NameAvailability::NotAllowed);
+        }
+
+        let res = ctx.client
+            .get(format!("{}/name/{}/available", ctx.endpoints.minecraft_profile, name))
+            .bearer_auth(minecraft_auth.access_token.expose())
+            .send_limited(ctx)
+            .await?;
+
+        let res: Response = AuthError::check_minecraft(res).await?.json().await?;
+        Ok(res.status)
+    }
+
+    /// Create the profile for the account which owns the game but doesn't have a profile yet.
+    ///
+    /// If the name can't be used, [MinecraftServicesError::NameUnavailable] will be returned.
+    ///
+    /// # Arguments
+    /// * `ctx` - The HTTP context
+    /// * `minecraft_auth` - The Minecraft Auth you get from [MinecraftAuth::fetch](crate::auth::minecraft::MinecraftAuth::fetch)
+    /// * `name` - The name of the new profile
+    pub async fn create(ctx: &HttpContext, minecraft_auth: &MinecraftAuth, name: &str) -> Result<Profile, AuthError> {
+        let res = ctx.client
+            .post(&ctx.endpoints.minecraft_profile)
+            .bearer_auth(minecraft_auth.access_token.expose())
+            .json(&json!({
+                "profileName": name
+            }))
+            .send_limited(ctx)
+            .await?;
+
+        name_response(res).await
+    }
+
+    /// Fetch whether the profile can change its name.
+    ///
+    /// # Arguments
+    /// * `ctx` - The HTTP context
+    /// * `minecraft_auth` - The Minecraft Auth you get from [MinecraftAuth::fetch](crate::auth::minecraft::MinecraftAuth::fetch)
+    pub async fn name_change_info(ctx: &HttpContext, minecraft_auth: &MinecraftAuth) -> Result<NameChangeInfo, AuthError> {
+        let res = ctx.client
+            .get(format!("{}/namechange", ctx.endpoints.minecraft_profile))
+            .bearer_auth(minecraft_auth.access_token.expose())
+            .send_limited(ctx)
+            .await?;
+
+        Ok(AuthError::check_minecraft(res).await?.json().await?)
+    }
+
+    /// Change the name of the profile.
+    ///
+    /// If the name can't be used, [MinecraftServicesError::NameUnavailable] will be returned.
+    /// Return the updated profile.
+    ///
+    /// # Arguments
+    /// * `ctx` - The HTTP context
+    /// * `minecraft_auth` - The Minecraft Auth you get from [MinecraftAuth::fetch](crate::auth::minecraft::MinecraftAuth::fetch)
+    /// * `name` - The new name
+    pub async fn change_name(ctx: &HttpContext, minecraft_auth: &MinecraftAuth, name: &str) -> Result<Profile, AuthError> {
+        if !is_valid_name(name) {
+            return Err(MinecraftServicesError::NameUnavailable(NameAvailability::NotAllowed).into());
+        }
+
+        let res = ctx.client
+            .put(format!("{}/name/{}", ctx.endpoints.minecraft_profile, name))
+            .bearer_auth(minecraft_auth.access_token.expose())
+            .send_limited(ctx)
+            .await?;
+
+        name_response(res).await
+    }
+
+}
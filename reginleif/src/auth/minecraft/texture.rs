@@ -0,0 +1,206 @@
+use std::io::Cursor;
+use std::path::Path;
+use image::{imageops, ImageFormat, Rgba, RgbaImage};
+use image::imageops::FilterType;
+use reqwest::Url;
+use reginleif_utils::save_path::{try_cache_bytes, BaseStorePoint};
+use crate::auth::minecraft::{Skin, SkinVariant};
+use crate::http::HttpContext;
+
+/// Skin Texture
+///
+/// The decoded skin image, which is always 64x64. The legacy 64x32 skins are converted
+/// to the 64x64 layout the same way as the game does.
+///
+/// # Example
+/// ```no_run
+/// use std::path::PathBuf;
+/// use reginleif::auth::minecraft::Profile;
+/// use reginleif::auth::minecraft::texture::SkinTexture;
+/// use reginleif::http::HttpContext;
+/// use reginleif_macro::BaseStorePoint;
+///
+/// #[derive(BaseStorePoint,Clone,Debug)]
+/// struct LauncherPath(PathBuf);
+///
+/// async fn example(profile:Profile){
+///     let base = LauncherPath(PathBuf::from("launcher"));
+///     let skin = profile.active_skin().unwrap();
+///
+///     let texture = SkinTexture::fetch(&base,&HttpContext::default(),skin).await.unwrap();
+///     let head = texture.render_head(64); // 64x64
+///     let body = texture.render_body(128); // 64x128
+///     head.save("head.png").unwrap();
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkinTexture {
+    image: RgbaImage,
+}
+
+impl SkinTexture {
+
+    /// Create the texture from the image, the legacy 64x32 skin will be converted to 64x64.
+    ///
+    /// # Arguments
+    /// * `image` - The skin image, must be 64x64 or 64x32.
+    pub fn new(image: RgbaImage) -> anyhow::Result<Self> {
+        match image.dimensions() {
+            (64, 64) => Ok(Self { image }),
+            (64, 32) => Ok(Self { image: convert_legacy(&image) }),
+            (w, h) => Err(anyhow::anyhow!("Invalid skin size {w}x{h}, expected 64x64 or 64x32")),
+        }
+    }
+
+    /// Decode the skin from the PNG file.
+    ///
+    /// # Arguments
+    /// * `png` - The content of the PNG file.
+    pub fn from_png(png: &[u8]) -> anyhow::Result<Self> {
+        let image = image::load_from_memory_with_format(png, ImageFormat::Png)?.to_rgba8();
+        Self::new(image)
+    }
+
+    /// Fetch the skin, it will be cached in `skins/<texture hash>.png` under the base path.
+    ///
+    /// The texture on textures.minecraft.net never changes with the same hash,
+    /// so the cached file won't be fetched again unless it can't be decoded.
+    ///
+    /// # Arguments
+    /// * `base_on` - The base path of the cache.
+    /// * `ctx` - The HTTP context.
+    /// * `skin` - The skin from [Profile](crate::auth::minecraft::Profile).
+    pub async fn fetch<T: BaseStorePoint>(base_on: &T, ctx: &HttpContext, skin: &Skin) -> anyhow::Result<Self> {
+        Self::fetch_url(base_on, ctx, &skin.url).await
+    }
+
+    /// Like [fetch](SkinTexture::fetch), but fetch the skin from the url directly.
+    ///
+    /// # Arguments
+    /// * `base_on` - The base path of the cache.
+    /// * `ctx` - The HTTP context.
+    /// * `url` - The url of the skin, the last segment of the path is used as the file name,
+    ///   so it must be alphanumeric like the texture hash.
+    pub async fn fetch_url<T: BaseStorePoint>(base_on: &T, ctx: &HttpContext, url: &str) -> anyhow::Result<Self> {
+        let parsed = Url::parse(url)?;
+        let hash = parsed.path_segments()
+            .and_then(|mut segments| segments.rfind(|x| !x.is_empty()))
+            .filter(|x| x.chars().all(|c| c.is_ascii_alphanumeric()))
+            .ok_or_else(|| anyhow::anyhow!("Invalid skin url {url}"))?;
+
+        let suffix = Path::new("skins").join(format!("{hash}.png"));
+        let png = try_cache_bytes(base_on, &suffix, &ctx.client, url).await?;
+        match Self::from_png(&png) {
+            Ok(texture) => Ok(texture),
+            Err(_) => { // the cached file is broken, remove it and download again.
+                tokio::fs::remove_file(base_on.get_base().join(&suffix)).await?;
+                let png = try_cache_bytes(base_on, &suffix, &ctx.client, url).await?;
+                Self::from_png(&png)
+            }
+        }
+    }
+
+    /// The 64x64 skin image.
+    pub fn image(&self) -> &RgbaImage {
+        &self.image
+    }
+
+    /// Encode the 64x64 skin to PNG, it can be uploaded by
+    /// [Profile::upload_skin](crate::auth::minecraft::Profile::upload_skin).
+    pub fn to_png(&self) -> anyhow::Result<Vec<u8>> {
+        let mut buf = Cursor::new(Vec::new());
+        self.image.write_to(&mut buf, ImageFormat::Png)?;
+        Ok(buf.into_inner())
+    }
+
+    /// Detect the model of the skin.
+    ///
+    /// The arms of the slim skin are 3px wide, so the last 2 columns of the right arm (x 54-55, y 20-31)
+    /// are unused and transparent.
+    pub fn variant(&self) -> SkinVariant {
+        let unused = (54..56).flat_map(|x| (20..32).map(move |y| (x, y)));
+        if unused.into_iter().all(|(x, y)| self.image.get_pixel(x, y)[3] == 0) {
+            SkinVariant::Slim
+        } else {
+            SkinVariant::Classic
+        }
+    }
+
+    /// Render the front of the head with the hat overlay.
+    ///
+    /// # Arguments
+    /// * `size` - The width and height of the rendered image.
+    pub fn render_head(&self, size: u32) -> RgbaImage {
+        let mut canvas = RgbaImage::new(8, 8);
+        self.draw(&mut canvas, (8, 8), (40, 8), (8, 8), (0, 0));
+        imageops::resize(&canvas, size, size, FilterType::Nearest)
+    }
+
+    /// Render the front of the whole body with the overlay, the model is detected by [variant](SkinTexture::variant).
+    ///
+    /// # Arguments
+    /// * `height` - The height of the rendered image, the width is half of it.
+    pub fn render_body(&self, height: u32) -> RgbaImage {
+        let arm = match self.variant() {
+            SkinVariant::Classic => 4,
+            SkinVariant::Slim => 3,
+        };
+
+        let mut canvas = RgbaImage::new(16, 32);
+        self.draw(&mut canvas, (8, 8), (40, 8), (8, 8), (4, 0)); // head
+        self.draw(&mut canvas, (20, 20), (20, 36), (8, 12), (4, 8)); // body
+        self.draw(&mut canvas, (44, 20), (44, 36), (arm, 12), (4 - arm, 8)); // right arm
+        self.draw(&mut canvas, (36, 52), (52, 52), (arm, 12), (12, 8)); // left arm
+        self.draw(&mut canvas, (4, 20), (4, 36), (4, 12), (4, 20)); // right leg
+        self.draw(&mut canvas, (20, 52), (4, 52), (4, 12), (8, 20)); // left leg
+        imageops::resize(&canvas, height / 2, height, FilterType::Nearest)
+    }
+
+    /// Draw a part of the skin to the canvas, the base layer is opaque like in game, and the overlay is blended on it.
+    fn draw(&self, canvas: &mut RgbaImage, base: (u32, u32), overlay: (u32, u32), (w, h): (u32, u32), (x, y): (u32, u32)) {
+        let mut part = imageops::crop_imm(&self.image, base.0, base.1, w, h).to_image();
+        part.pixels_mut().for_each(|p| p[3] = 255);
+        imageops::replace(canvas, &part, x as i64, y as i64);
+
+        let part = imageops::crop_imm(&self.image, overlay.0, overlay.1, w, h).to_image();
+        imageops::overlay(canvas, &part, x as i64, y as i64);
+    }
+
+}
+
+/// Convert the legacy 64x32 skin to 64x64, the left arm and leg are mirrored from the right ones.
+fn convert_legacy(legacy: &RgbaImage) -> RgbaImage {
+    let mut image = RgbaImage::new(64, 64);
+    imageops::replace(&mut image, legacy, 0, 0);
+
+    // (x, y, w, h, dx, dy), copy the rect at (x, y) to (x + dx, y + dy) and mirror it horizontally.
+    const RECTS: [(u32, u32, u32, u32, i32, i32); 12] = [
+        (4, 16, 4, 4, 16, 32), (8, 16, 4, 4, 16, 32), // leg top and bottom
+        (0, 20, 4, 12, 24, 32), (4, 20, 4, 12, 16, 32), (8, 20, 4, 12, 8, 32), (12, 20, 4, 12, 16, 32), // leg sides
+        (44, 16, 4, 4, -8, 32), (48, 16, 4, 4, -8, 32), // arm top and bottom
+        (40, 20, 4, 12, 0, 32), (44, 20, 4, 12, -8, 32), (48, 20, 4, 12, -16, 32), (52, 20, 4, 12, -8, 32), // arm sides
+    ];
+
+    for (x, y, w, h, dx, dy) in RECTS {
+        for i in 0..w {
+            for j in 0..h {
+                let pixel: Rgba<u8> = *legacy.get_pixel(x + i, y + j);
+                let to_x = (x as i32 + dx) as u32 + (w - 1 - i);
+                let to_y = (y as i32 + dy) as u32 + j;
+                image.put_pixel(to_x, to_y, pixel);
+            }
+        }
+    }
+
+    // Some legacy skins fill the hat with opaque color, which should be treated as no hat.
+    let hat = imageops::crop_imm(legacy, 32, 0, 32, 16).to_image();
+    if hat.pixels().all(|p| p[3] == 255) {
+        for x in 32..64 {
+            for y in 0..16 {
+                image.put_pixel(x, y, Rgba([0, 0, 0, 0]));
+            }
+        }
+    }
+
+    image
+}
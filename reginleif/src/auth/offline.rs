@@ -0,0 +1,53 @@
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+use uuid::Builder;
+
+/// Offline Account
+///
+/// The account which doesn't login to any service, it can only be used in LAN play,
+/// servers with `online-mode=false` and development.
+///
+/// The uuid is derived from the name in the same way as Minecraft server (`OfflinePlayer:<name>`),
+/// so the same name always gets the same uuid. The account never expires.
+///
+/// # Example
+/// ```
+/// use reginleif::auth::offline::OfflineAccount;
+///
+/// let account = OfflineAccount::new("Notch");
+/// assert_eq!(account.uuid,"b50ad385829d3141a2167e7d7539ba7f");
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct OfflineAccount {
+    /// name of the player
+    pub name: String,
+    /// uuid of the player, without hyphens like [Profile::id](crate::auth::minecraft::Profile::id).
+    pub uuid: String,
+}
+
+impl OfflineAccount {
+
+    /// Create an offline account with the uuid derived from the name.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the player.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            uuid: Self::uuid_of(name),
+        }
+    }
+
+    /// Get the offline uuid of the name.
+    ///
+    /// This is the version 3 uuid of `OfflinePlayer:<name>` without namespace,
+    /// which is the same as `UUID.nameUUIDFromBytes` in Java.
+    pub fn uuid_of(name: &str) -> String {
+        let hash = Md5::digest(format!("OfflinePlayer:{}", name).as_bytes());
+        Builder::from_md5_bytes(hash.into())
+            .into_uuid()
+            .simple()
+            .to_string()
+    }
+
+}
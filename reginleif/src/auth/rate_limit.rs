@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Utc};
+use reqwest::{RequestBuilder, Response, StatusCode, Url};
+use reqwest::header::RETRY_AFTER;
+use crate::http::HttpContext;
+
+/// The config of [RateLimiter].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitConfig {
+    /// The maximum number of requests to one host that can be sent at once.
+    pub capacity: u32,
+    /// One more request can be sent after this long, until it reaches [capacity](RateLimitConfig::capacity).
+    pub refill_interval: Duration,
+    /// Retry the request at most this many times when the server responds 429.
+    pub max_retries: u32,
+    /// The delay before the first retry if the server doesn't send `Retry-After`, it doubles on every retry.
+    pub min_backoff: Duration,
+    /// Don't retry if the server asks to wait longer than this, the 429 response is returned instead,
+    /// so the caller can decide what to do, like [RefreshScheduler](crate::auth::scheduler::RefreshScheduler).
+    pub max_wait: Duration,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 10,
+            refill_interval: Duration::from_millis(500),
+            max_retries: 3,
+            min_backoff: Duration::from_secs(1),
+            max_wait: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RateLimitConfig {
+
+    /// The delay before the `attempt`-th retry (starting from 1).
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.min_backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_wait)
+    }
+
+}
+
+/// The token bucket of one host, the tokens can be negative when the requests are waiting.
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last: Instant,
+}
+
+/// Rate Limiter
+///
+/// This struct limits the requests to each host with a token bucket, and retries the request
+/// with exponential backoff when the server responds 429, respecting the `Retry-After` header.
+///
+/// All the requests of the auth module are sent through the [limiter](HttpContext::limiter) of the context,
+/// which is shared by the clones of the context, so refreshing several accounts at once won't hit
+/// the rate limit of Minecraft services.
+///
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+/// use reginleif::auth::rate_limit::RateLimitConfig;
+/// use reginleif::http::HttpContext;
+///
+/// let ctx = HttpContext::builder()
+///     .rate_limit(RateLimitConfig{
+///         max_wait: Duration::from_secs(30),
+///         ..RateLimitConfig::default()
+///     })
+///     .build()
+///     .unwrap();
+///
+/// // or change it later, the clones of the context are affected too.
+/// ctx.limiter.configure(RateLimitConfig::default());
+/// ```
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    config: Mutex<RateLimitConfig>,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+
+    /// Create a limiter with the config.
+    ///
+    /// # Arguments
+    /// * `config`: The config of the limiter.
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config: Mutex::new(config),
+            buckets: Default::default(),
+        }
+    }
+
+    /// Replace the config, the waiting requests are not affected.
+    ///
+    /// # Arguments
+    /// * `config`: The new config.
+    pub fn configure(&self, config: RateLimitConfig) {
+        *self.config.lock().unwrap() = config;
+    }
+
+    /// The current config.
+    pub fn config(&self) -> RateLimitConfig {
+        self.config.lock().unwrap().clone()
+    }
+
+    /// Send the request after the host has a free token, and retry it if the server responds 429.
+    ///
+    /// The last response is returned as is, so the status should still be checked by the caller.
+    /// The request with a streaming body (like multipart) can't be cloned, so it won't be retried.
+    ///
+    /// # Arguments
+    /// * `builder`: The request to send.
+    pub async fn send(&self, builder: RequestBuilder) -> reqwest::Result<Response> {
+        let config = self.config();
+        let (client, request) = builder.build_split();
+        let mut request = request?;
+        let host = host_of(request.url());
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let next = if attempt <= config.max_retries { request.try_clone() } else { None };
+
+            tokio::time::sleep(self.reserve(&host, &config)).await;
+            let res = client.execute(request).await?;
+
+            let Some(next) = next else { return Ok(res) };
+            if res.status() != StatusCode::TOO_MANY_REQUESTS {
+                return Ok(res);
+            }
+
+            let wait = retry_after(&res).unwrap_or_else(|| config.backoff(attempt));
+            if wait > config.max_wait {
+                return Ok(res);
+            }
+
+            self.pause(&host, wait, &config);
+            request = next;
+        }
+    }
+
+    /// Take a token of the host, return how long to wait before sending the request.
+    fn reserve(&self, host: &str, config: &RateLimitConfig) -> Duration {
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = Self::refill(&mut buckets, host, config);
+
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            config.refill_interval.mul_f64(-bucket.tokens)
+        }
+    }
+
+    /// Stop sending the requests to the host for `wait`, including the ones from other tasks.
+    fn pause(&self, host: &str, wait: Duration, config: &RateLimitConfig) {
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = Self::refill(&mut buckets, host, config);
+
+        let interval = config.refill_interval.as_secs_f64().max(f64::EPSILON);
+        bucket.tokens = bucket.tokens.min(0.0) - wait.as_secs_f64() / interval;
+    }
+
+    fn refill<'a>(buckets: &'a mut HashMap<String, Bucket>, host: &str, config: &RateLimitConfig) -> &'a mut Bucket {
+        let capacity = config.capacity.max(1) as f64;
+        let bucket = buckets.entry(host.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            last: Instant::now(),
+        });
+
+        let now = Instant::now();
+        let interval = config.refill_interval.as_secs_f64().max(f64::EPSILON);
+        bucket.tokens = (bucket.tokens + (now - bucket.last).as_secs_f64() / interval).min(capacity);
+        bucket.last = now;
+        bucket
+    }
+
+}
+
+/// Send the request through the [limiter](HttpContext::limiter) of the context.
+pub(crate) trait SendLimited {
+    async fn send_limited(self, ctx: &HttpContext) -> reqwest::Result<Response>;
+}
+
+impl SendLimited for RequestBuilder {
+    async fn send_limited(self, ctx: &HttpContext) -> reqwest::Result<Response> {
+        ctx.limiter.send(self).await
+    }
+}
+
+/// Parse the `Retry-After` header, which is either the seconds to wait or an HTTP date.
+pub(crate) fn retry_after(res: &Response) -> Option<Duration> {
+    let value = res.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    // the date in the past means no need to wait.
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some((date.with_timezone(&Utc) - Utc::now()).to_std().unwrap_or_default())
+}
+
+fn host_of(url: &Url) -> String {
+    match url.port_or_known_default() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+        None => url.host_str().unwrap_or_default().to_string(),
+    }
+}
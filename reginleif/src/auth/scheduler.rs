@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use chrono::Local;
+use tokio::sync::{broadcast, Mutex};
+use tokio_util::sync::CancellationToken;
+use crate::auth::account::AccountKind;
+use crate::auth::error::{AuthError, MinecraftServicesError};
+use crate::http::HttpContext;
+
+/// The account shared between the scheduler and the launcher.
+pub type SharedAccount = Arc<Mutex<AccountKind>>;
+
+/// The tasks keyed by the uuid of the player.
+type Tasks = Arc<std::sync::Mutex<HashMap<String, CancellationToken>>>;
+
+/// The longest sleep before checking the expiry again, so the task catches up soon after the computer wakes up.
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+
+/// The config of [RefreshScheduler].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefreshConfig {
+    /// Refresh the account this long before it expires.
+    pub margin: Duration,
+    /// The delay before the first retry, it doubles on every retry.
+    pub min_backoff: Duration,
+    /// The maximum delay between retries.
+    pub max_backoff: Duration,
+    /// Give up after this many retries in a row.
+    pub max_retries: u32,
+    /// Validate the Yggdrasil token this often, because it doesn't have an expiry time.
+    pub validate_interval: Duration,
+}
+
+impl Default for RefreshConfig {
+    fn default() -> Self {
+        Self {
+            margin: Duration::from_secs(5 * 60),
+            min_backoff: Duration::from_secs(30),
+            max_backoff: Duration::from_secs(10 * 60),
+            max_retries: 5,
+            validate_interval: Duration::from_secs(30 * 60),
+        }
+    }
+}
+
+impl RefreshConfig {
+
+    /// The delay before the `attempt`-th retry (starting from 1).
+    fn backoff(&self, attempt: u32, error: &AuthError) -> Duration {
+        let backoff = self.min_backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff);
+
+        match error {
+            AuthError::Minecraft(MinecraftServicesError::TooManyRequests { retry_after: Some(x) }) => backoff.max(*x),
+            _ => backoff,
+        }
+    }
+
+}
+
+/// The event emitted by [RefreshScheduler].
+///
+/// The uuid is the uuid of the player.
+#[derive(Debug, Clone)]
+pub enum RefreshEvent {
+    /// The account is refreshed, you may want to put it into [AccountManager](crate::auth::manager::AccountManager)
+    /// and save it.
+    Refreshed {
+        uuid: String,
+        account: Box<AccountKind>,
+    },
+    /// The refresh failed with a transient error and will be retried.
+    RetryScheduled {
+        uuid: String,
+        /// The number of the retry, starting from 1.
+        attempt: u32,
+        retry_in: Duration,
+        error: Arc<AuthError>,
+    },
+    /// The Microsoft or Yggdrasil token can't be refreshed anymore, the user must login again.
+    ///
+    /// The scheduler stops watching the account, you can [register](RefreshScheduler::register) it again after login.
+    NeedsRelogin {
+        uuid: String,
+        error: Arc<AuthError>,
+    },
+    /// The refresh failed and won't be retried, the scheduler stops watching the account.
+    Failed {
+        uuid: String,
+        error: Arc<AuthError>,
+    },
+}
+
+/// Refresh Scheduler
+///
+/// This struct watches the registered accounts, so the account is always ready when the user clicks Play.
+/// The Microsoft account is refreshed [margin](RefreshConfig::margin) before it expires, and the Yggdrasil token
+/// is validated every [validate_interval](RefreshConfig::validate_interval) and refreshed if it's invalid.
+/// The offline account never expires, so it's not watched.
+///
+/// Every account is watched by a spawned tokio task, so it must be used inside a tokio runtime.
+/// All the tasks are stopped when the scheduler is dropped.
+///
+/// # Example
+/// ```no_run
+/// use std::sync::Arc;
+/// use tokio::sync::Mutex;
+/// use reginleif::auth::manager::AccountManager;
+/// use reginleif::auth::scheduler::{RefreshConfig, RefreshEvent, RefreshScheduler};
+/// use reginleif::http::HttpContext;
+/// use reginleif_utils::save_path::{BaseStorePoint, Store};
+///
+/// async fn example<T:BaseStorePoint>(base:&T, mut manager:AccountManager<T>){
+///     let scheduler = RefreshScheduler::new(HttpContext::default(),"your_client_id",RefreshConfig::default());
+///     let mut events = scheduler.subscribe();
+///
+///     for account in manager.accounts() {
+///         scheduler.register(Arc::new(Mutex::new(account.clone()))).await;
+///     }
+///
+///     while let Ok(event) = events.recv().await {
+///         match event {
+///             RefreshEvent::Refreshed{account,..} => {
+///                 manager.add(*account);
+///                 manager.save(base).unwrap();
+///             }
+///             other => println!("{:?}",other),
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct RefreshScheduler {
+    ctx: HttpContext,
+    client_id: String,
+    config: RefreshConfig,
+    events: broadcast::Sender<RefreshEvent>,
+    tasks: Tasks,
+    cancel: CancellationToken,
+}
+
+impl RefreshScheduler {
+
+    /// Create a new scheduler.
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    /// * `client_id`: The client id of your app.
+    /// * `config`: The config of the scheduler.
+    pub fn new(ctx: HttpContext, client_id: &str, config: RefreshConfig) -> Self {
+        let (events, _) = broadcast::channel(32);
+        Self {
+            ctx,
+            client_id: client_id.to_string(),
+            config,
+            events,
+            tasks: Default::default(),
+            cancel: CancellationToken::new(),
+        }
+    }
+
+    /// Subscribe the events.
+    pub fn subscribe(&self) -> broadcast::Receiver<RefreshEvent> {
+        self.events.subscribe()
+    }
+
+    /// Start watching the account, the old one with the same uuid will be replaced.
+    ///
+    /// The offline account is ignored, because it never expires.
+    ///
+    /// # Arguments
+    /// * `account`: The account to watch.
+    pub async fn register(&self, account: SharedAccount) {
+        let uuid = match &*account.lock().await {
+            AccountKind::Offline(_) => return,
+            account => account.uuid().to_string(),
+        };
+        let token = self.cancel.child_token();
+
+        if let Some(old) = self.tasks.lock().unwrap().insert(uuid.clone(), token.clone()) {
+            old.cancel();
+        }
+
+        let task = RefreshTask {
+            uuid,
+            account,
+            tasks: self.tasks.clone(),
+            token: token.clone(),
+            ctx: self.ctx.clone(),
+            client_id: self.client_id.clone(),
+            config: self.config.clone(),
+            events: self.events.clone(),
+        };
+
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = token.cancelled() => {},
+                _ = task.run() => {},
+            }
+        });
+    }
+
+    /// Stop watching the account, return `false` if the account is not registered.
+    ///
+    /// # Arguments
+    /// * `uuid`: The uuid of the player.
+    pub fn unregister(&self, uuid: &str) -> bool {
+        match self.tasks.lock().unwrap().remove(uuid) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop watching all the accounts.
+    pub fn shutdown(&self) {
+        self.cancel.cancel();
+        self.tasks.lock().unwrap().clear();
+    }
+
+}
+
+impl Drop for RefreshScheduler {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+/// The task watching one account.
+struct RefreshTask {
+    uuid: String,
+    account: SharedAccount,
+    tasks: Tasks,
+    token: CancellationToken,
+    ctx: HttpContext,
+    client_id: String,
+    config: RefreshConfig,
+    events: broadcast::Sender<RefreshEvent>,
+}
+
+impl RefreshTask {
+
+    async fn run(self) {
+        let mut attempt = 0;
+        // the last time the Yggdrasil token is known to be valid.
+        let mut checked_at = Instant::now();
+        loop {
+            // the account may be refreshed or replaced by the launcher while sleeping, so check it again after waking up.
+            loop {
+                let Some(wait) = self.wait(&*self.account.lock().await, checked_at) else {
+                    self.finish();
+                    return;
+                };
+                if wait.is_zero() {
+                    break;
+                }
+                attempt = 0;
+                tokio::time::sleep(wait.min(MAX_SLEEP)).await;
+            }
+
+            // renew a copy, so the launcher can still use the account during the requests.
+            let snapshot = self.account.lock().await.clone();
+            let mut account = snapshot.clone();
+            let res = renew(&mut account, &self.ctx, &self.client_id).await;
+
+            {
+                let mut shared = self.account.lock().await;
+                // the launcher refreshed or logged in again during the requests, keep its account and check it again.
+                if !same_session(&shared, &snapshot) {
+                    checked_at = Instant::now();
+                    continue;
+                }
+                if let Ok(true) = res {
+                    *shared = account.clone();
+                }
+            }
+
+            let uuid = self.uuid.clone();
+            match res {
+                Ok(refreshed) => {
+                    attempt = 0;
+                    checked_at = Instant::now();
+                    if refreshed {
+                        let _ = self.events.send(RefreshEvent::Refreshed { uuid, account: Box::new(account) });
+                    }
+                }
+                Err(e) if e.needs_relogin() => {
+                    self.finish();
+                    let _ = self.events.send(RefreshEvent::NeedsRelogin { uuid, error: Arc::new(e) });
+                    return;
+                }
+                Err(e) if e.is_retryable() && attempt < self.config.max_retries => {
+                    attempt += 1;
+                    let retry_in = self.config.backoff(attempt, &e);
+                    let _ = self.events.send(RefreshEvent::RetryScheduled { uuid, attempt, retry_in, error: Arc::new(e) });
+                    tokio::time::sleep(retry_in).await;
+                }
+                Err(e) => {
+                    self.finish();
+                    let _ = self.events.send(RefreshEvent::Failed { uuid, error: Arc::new(e) });
+                    return;
+                }
+            }
+        }
+    }
+
+    /// The time to wait before renewing the account, `None` if the account doesn't need to be watched.
+    fn wait(&self, account: &AccountKind, checked_at: Instant) -> Option<Duration> {
+        match account {
+            AccountKind::Microsoft(x) => Some(x.expire_in().saturating_sub(self.config.margin)),
+            AccountKind::Yggdrasil(_) => Some(self.config.validate_interval.saturating_sub(checked_at.elapsed())),
+            AccountKind::Offline(_) => None,
+        }
+    }
+
+    /// Remove the task from the scheduler, unless the account is registered again by a newer task.
+    fn finish(&self) {
+        let mut tasks = self.tasks.lock().unwrap();
+        if !self.token.is_cancelled() {
+            tasks.remove(&self.uuid);
+        }
+    }
+
+}
+
+/// Renew the account, return `false` if the Yggdrasil token is still valid and nothing is changed.
+async fn renew(account: &mut AccountKind, ctx: &HttpContext, client_id: &str) -> Result<bool, AuthError> {
+    match account {
+        AccountKind::Microsoft(x) => {
+            x.data.renew(ctx, client_id).await?;
+            x.created_at = Local::now();
+        }
+        AccountKind::Yggdrasil(x) if x.validate(ctx).await? => return Ok(false),
+        AccountKind::Yggdrasil(x) => x.renew(ctx).await?,
+        AccountKind::Offline(_) => return Ok(false),
+    }
+    Ok(true)
+}
+
+/// Check the account is still the one the renewal started from, the launcher may refresh or replace it meanwhile.
+fn same_session(current: &AccountKind, snapshot: &AccountKind) -> bool {
+    match (current, snapshot) {
+        (AccountKind::Microsoft(current), AccountKind::Microsoft(snapshot)) => {
+            current.created_at == snapshot.created_at
+                && current.data.msa.data.refresh_token == snapshot.data.msa.data.refresh_token
+        }
+        (AccountKind::Yggdrasil(current), AccountKind::Yggdrasil(snapshot)) => current.access_token == snapshot.access_token,
+        _ => false,
+    }
+}
@@ -1,25 +1,35 @@
-use anyhow::anyhow;
-use reqwest::Client;
+use reqwest::StatusCode;
 use serde_json::{json, Value};
 use thiserror::Error;
-use crate::auth::constant::{XBOX_USER_AUTHENTICATE, XBOX_XSTS_AUTHORIZE};
+use reginleif_utils::secret::SecretString;
+use crate::auth::error::AuthError;
 use crate::auth::xbox::XboxSecurityError::Others;
+use crate::auth::rate_limit::SendLimited;
+use crate::http::HttpContext;
+
+/// The Xbox profile of the user, including gamertag and gamerpic.
+pub mod profile;
+
+/// The relying party of the XSTS token which is used to login Minecraft.
+pub const MINECRAFT_RELYING_PARTY: &str = "rp://api.minecraftservices.com/";
+/// The relying party of the XSTS token which is used to call Xbox Live services, like [XboxProfile](profile::XboxProfile).
+pub const XBOX_LIVE_RELYING_PARTY: &str = "http://xboxlive.com";
 
 /// Xbox Live Token
 /// 
 /// This token is used to authenticate with Xbox Security Token.
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
-pub struct XboxLiveToken(String);
+pub struct XboxLiveToken(SecretString);
 
 impl From<&str> for XboxLiveToken {
     fn from(s: &str) -> Self {
-        Self(s.to_string())
+        Self(s.into())
     }
 }
 
 impl From<XboxLiveToken> for String {
     fn from(token: XboxLiveToken) -> Self {
-        token.0
+        token.0.expose().to_string()
     }
 }
 
@@ -30,10 +40,10 @@ impl XboxLiveToken {
     /// This function will fetch Xbox Live Token from the given token.
     /// 
     /// # Arguments
-    /// * `client` - The reqwest client
+    /// * `ctx` - The HTTP context
     /// * `token` - The microsoft auth token you get from [MicrosoftAuth](crate::auth::microsoft::MicrosoftAuth)
     /// 
-    pub async fn fetch(client:&Client,token:&str) -> anyhow::Result<XboxLiveToken>{
+    pub async fn fetch(ctx:&HttpContext,token:&str) -> Result<XboxLiveToken,AuthError>{
 
         let xbox_authenticate_json = json!({
            "Properties": {
@@ -45,18 +55,19 @@ impl XboxLiveToken {
             "TokenType": "JWT"
         });
 
-        let res = client.post(XBOX_USER_AUTHENTICATE)
+        let res = ctx.client.post(&ctx.endpoints.xbox_user_authenticate)
             .json(&xbox_authenticate_json)
-            .send()
-            .await?
-            .error_for_status()?;
+            .send_limited(ctx)
+            .await?;
 
-        let token = res.json::<Value>()
+        let token = AuthError::check_status(res)
+            .await?
+            .json::<Value>()
             .await?
             .get("Token")
-            .ok_or(anyhow!("Token not found"))?
+            .ok_or(AuthError::InvalidResponse("Token not found".to_string()))?
             .as_str()
-            .ok_or(anyhow!("Error while parsing token"))?
+            .ok_or(AuthError::InvalidResponse("Error while parsing token".to_string()))?
             .into();
 
         Ok(token)
@@ -68,33 +79,78 @@ impl XboxLiveToken {
 /// This token is used to authenticate the user with Minecraft Auth Server.
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
 pub struct XboxSecurityToken{
-    pub token: String,
+    pub token: SecretString,
     pub uhs: String,
 }
 
 impl From<(&str,&str)> for XboxSecurityToken{
     fn from((token,uhs): (&str, &str)) -> Self {
         Self{
-            token: token.to_string(),
+            token: token.into(),
             uhs: uhs.to_string(),
         }
     }
 }
 
 
+/// The details of XErr returned by Xbox Security Token Service.
+///
+/// see [wiki.vg](https://wiki.vg/Microsoft_Authentication_Scheme#Authenticate_with_XSTS)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct XErr{
+    /// The raw XErr code, like 2148916233.
+    pub code: u64,
+    /// The `Message` field of the response, it's empty most of the time.
+    pub message: Option<String>,
+    /// The `Redirect` field of the response, which is the page the user can fix the account.
+    pub redirect: Option<String>,
+}
+
+impl std::fmt::Display for XErr{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "XErr: {}", self.code)?;
+        if let Some(message) = &self.message {
+            write!(f, ", message: {}", message)?;
+        }
+        if let Some(redirect) = &self.redirect {
+            write!(f, ", see: {}", redirect)?;
+        }
+        Ok(())
+    }
+}
+
 /// The error that can occur while fetching Xbox Security Token.
+///
+/// All the XErr variants carry the [XErr] details, you can use [redirect](XboxSecurityError::redirect)
+/// to send the user to the page to fix the account.
 #[derive(Error,Debug)]
 pub enum XboxSecurityError{
-    #[error("The account doesn't have an Xbox account. Once they sign up for one (or login through minecraft.net to create one) then they can proceed with the login")]
-    NotExist,
-    #[error("The account is banned from Xbox Live due to your country")]
-    CountryBan,
-    #[error("The account needs adult verification on Xbox page")]
-    NeedAdultVerification,
-    #[error("The account is a child (under 18) and cannot proceed unless the account is added to a Family by an adult.")]
-    AddToFamily,
+    #[error("The account is banned by Xbox for violating the Community Standards. ({0})")]
+    Banned(XErr),
+    #[error("The account is restricted and the guardian has not given permission to play online. ({0})")]
+    ParentalRestriction(XErr),
+    #[error("The account doesn't have an Xbox account. Once they sign up for one (or login through minecraft.net to create one) then they can proceed with the login ({0})")]
+    NotExist(XErr),
+    #[error("The account has not accepted Xbox's Terms of Service. ({0})")]
+    TermsNotAccepted(XErr),
+    #[error("The account is banned from Xbox Live due to your country ({0})")]
+    CountryBan(XErr),
+    #[error("The account needs adult verification on Xbox page ({0})")]
+    NeedAdultVerification(XErr),
+    #[error("The account is a child (under 18) and cannot proceed unless the account is added to a Family by an adult. ({0})")]
+    AddToFamily(XErr),
+    #[error("Unknown XErr from Xbox Security Token Service. ({0})")]
+    Unknown(XErr),
     #[error("Error while fetching Xbox Security Token. Details: {0}")]
     Others(String),
+    /// The server responds an error status without XErr.
+    #[error("Unexpected HTTP status {status} from Xbox Security Token Service. body:{body}")]
+    Status {
+        /// The status of the response.
+        status: StatusCode,
+        /// The body of the response.
+        body: String,
+    },
     #[error("Reqwest error. Details: {0}")]
     ReqwestError(reqwest::Error)
 
@@ -106,30 +162,89 @@ impl From<reqwest::Error> for XboxSecurityError{
     }
 }
 
+impl From<XErr> for XboxSecurityError{
+    /// Map the XErr code to the error.
+    fn from(xerr: XErr) -> Self {
+        match xerr.code {
+            2148916227 => Self::Banned(xerr),
+            2148916229 => Self::ParentalRestriction(xerr),
+            2148916233 => Self::NotExist(xerr),
+            2148916234 => Self::TermsNotAccepted(xerr),
+            2148916235 => Self::CountryBan(xerr),
+            2148916236 | 2148916237 => Self::NeedAdultVerification(xerr),
+            2148916238 => Self::AddToFamily(xerr),
+            _other => Self::Unknown(xerr),
+        }
+    }
+}
+
+impl XboxSecurityError{
+
+    /// The XErr details, return `None` if the error is not caused by XErr.
+    pub fn xerr(&self) -> Option<&XErr>{
+        match self {
+            Self::Banned(xerr)
+            | Self::ParentalRestriction(xerr)
+            | Self::NotExist(xerr)
+            | Self::TermsNotAccepted(xerr)
+            | Self::CountryBan(xerr)
+            | Self::NeedAdultVerification(xerr)
+            | Self::AddToFamily(xerr)
+            | Self::Unknown(xerr) => Some(xerr),
+            Self::Others(_) | Self::Status { .. } | Self::ReqwestError(_) => None,
+        }
+    }
+
+    /// The raw XErr code.
+    pub fn code(&self) -> Option<u64>{
+        self.xerr().map(|x| x.code)
+    }
+
+    /// The page the user can fix the account.
+    pub fn redirect(&self) -> Option<&str>{
+        self.xerr().and_then(|x| x.redirect.as_deref())
+    }
+
+}
+
 impl XboxSecurityToken{
     
     /// Fetch Xbox Security Token
     /// 
-    /// This function will fetch Xbox Security Token from the given Xbox Live Token.
+    /// This function will fetch Xbox Security Token from the given Xbox Live Token,
+    /// the token can be used to login Minecraft.
     /// 
     /// # Arguments
-    /// * `client` - The reqwest client
+    /// * `ctx` - The HTTP context
+    /// * `token` - The Xbox Live Token you get from [XboxLiveToken::fetch](XboxLiveToken::fetch)
+    pub async fn fetch(ctx:&HttpContext,token:XboxLiveToken) -> Result<XboxSecurityToken,XboxSecurityError> {
+        Self::fetch_for(ctx,token,MINECRAFT_RELYING_PARTY).await
+    }
+
+    /// Fetch Xbox Security Token for the given relying party.
+    ///
+    /// The token only works with the services of the relying party, like [XBOX_LIVE_RELYING_PARTY]
+    /// for [XboxProfile](profile::XboxProfile).
+    ///
+    /// # Arguments
+    /// * `ctx` - The HTTP context
     /// * `token` - The Xbox Live Token you get from [XboxLiveToken::fetch](XboxLiveToken::fetch)
-    pub async fn fetch(client:&Client,token:XboxLiveToken) -> Result<XboxSecurityToken,XboxSecurityError> {
+    /// * `relying_party` - The relying party of the token
+    pub async fn fetch_for(ctx:&HttpContext,token:XboxLiveToken,relying_party:&str) -> Result<XboxSecurityToken,XboxSecurityError> {
 
         let xbox_authenticate_json = json!({
             "Properties": {
                 "SandboxId": "RETAIL",
                 "UserTokens": [token.0],
             },
-            "RelyingParty": "rp://api.minecraftservices.com/",
+            "RelyingParty": relying_party,
             "TokenType": "JWT"
         });
 
-        let response = client
-            .post(XBOX_XSTS_AUTHORIZE)
+        let response = ctx.client
+            .post(&ctx.endpoints.xbox_xsts_authorize)
             .json(&xbox_authenticate_json)
-            .send()
+            .send_limited(ctx)
             .await?;
         
         if response.status().is_success() {
@@ -148,26 +263,26 @@ impl XboxSecurityToken{
             Ok((token,user_hash).into())
         } else {
 
-            let value = response
-                .json::<Value>()
-                .await?;
+            // the error body isn't always XErr json, like the html page of a gateway.
+            let status = response.status();
+            let body = response.text().await?;
+            let (code, value) = match serde_json::from_str::<Value>(&body) {
+                Ok(value) => match value["XErr"].as_u64() {
+                    Some(code) => (code, value),
+                    None => return Err(XboxSecurityError::Status{ status, body }),
+                },
+                Err(_) => return Err(XboxSecurityError::Status{ status, body }),
+            };
+
+            let non_empty = |x:&Value| x.as_str().filter(|x| !x.is_empty()).map(str::to_string);
 
-            match value["XErr"]
-                .as_u64().ok_or(Others("Error while fetching XErr code".to_string()))? {
-                2148916233 => Err(XboxSecurityError::NotExist),
-                2148916235 => Err(XboxSecurityError::CountryBan),
-                2148916236 | 2148916237 => {
-                    Err(XboxSecurityError::NeedAdultVerification)
-                }
-                2148916238 => Err(XboxSecurityError::AddToFamily),
-                _other => Err(Others(
-                    "Unknown Error".to_string(),
-                )),
-            }
+            Err(XErr{
+                code,
+                message: non_empty(&value["Message"]),
+                redirect: non_empty(&value["Redirect"]),
+            }.into())
 
         }
 
     }
 }
-
-
@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use crate::auth::error::AuthError;
+use crate::auth::microsoft::MicrosoftAuth;
+use crate::auth::xbox::{XboxLiveToken, XboxSecurityToken, XBOX_LIVE_RELYING_PARTY};
+use crate::auth::rate_limit::SendLimited;
+use crate::http::HttpContext;
+
+/// The settings requested from the profile service.
+const SETTINGS: &str = "Gamertag,GameDisplayPicRaw,UniqueModernGamertag";
+
+/// Xbox Profile
+///
+/// The Xbox profile of the user, which can be shown in the account list.
+///
+/// # Example
+/// ```no_run
+/// use reginleif::http::HttpContext;
+/// use reginleif::auth::microsoft::MicrosoftAuth;
+/// use reginleif::auth::xbox::profile::XboxProfile;
+///
+/// async fn example(msa:MicrosoftAuth){
+///     let ctx = HttpContext::default();
+///
+///     let profile = XboxProfile::fetch_with_msa(&ctx,&msa).await.unwrap();
+///     println!("{} {:?}",profile.gamertag,profile.gamerpic);
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct XboxProfile {
+    /// The Xbox user id.
+    pub xuid: String,
+    /// The classic gamertag.
+    pub gamertag: String,
+    /// The modern gamertag with the suffix, like `Player#1234`, if the user has one.
+    pub unique_modern_gamertag: Option<String>,
+    /// The url of the avatar image.
+    pub gamerpic: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfileResponse {
+    profile_users: Vec<ProfileUser>,
+}
+
+#[derive(Deserialize)]
+struct ProfileUser {
+    id: String,
+    settings: Vec<Setting>,
+}
+
+#[derive(Deserialize)]
+struct Setting {
+    id: String,
+    value: String,
+}
+
+impl XboxProfile {
+
+    /// Fetch the profile of the user.
+    ///
+    /// # Arguments
+    /// * `ctx` - The HTTP context
+    /// * `token` - The Xbox Security Token for [XBOX_LIVE_RELYING_PARTY], you get it from
+    ///   [XboxSecurityToken::fetch_for](XboxSecurityToken::fetch_for)
+    pub async fn fetch(ctx: &HttpContext, token: &XboxSecurityToken) -> Result<Self, AuthError> {
+        let res = ctx.client
+            .get(&ctx.endpoints.xbox_profile)
+            .query(&[("settings", SETTINGS)])
+            .header("Authorization", format!("XBL3.0 x={};{}", token.uhs, token.token.expose()))
+            .header("x-xbl-contract-version", "3")
+            .send_limited(ctx)
+            .await?;
+
+        let res: ProfileResponse = AuthError::check_status(res).await?.json().await?;
+        let user = res.profile_users
+            .into_iter()
+            .next()
+            .ok_or(AuthError::InvalidResponse("Profile user not found".to_string()))?;
+
+        let setting = |id: &str| user.settings
+            .iter()
+            .find(|x| x.id == id && !x.value.is_empty())
+            .map(|x| x.value.clone());
+
+        Ok(Self {
+            gamertag: setting("Gamertag").ok_or(AuthError::InvalidResponse("Gamertag not found".to_string()))?,
+            unique_modern_gamertag: setting("UniqueModernGamertag"),
+            gamerpic: setting("GameDisplayPicRaw"),
+            xuid: user.id,
+        })
+    }
+
+    /// Fetch the profile with the Microsoft token, the Xbox Live Token and the Xbox Security Token
+    /// for [XBOX_LIVE_RELYING_PARTY] are fetched on the way.
+    ///
+    /// # Arguments
+    /// * `ctx` - The HTTP context
+    /// * `msa` - The Microsoft Auth of the user
+    pub async fn fetch_with_msa(ctx: &HttpContext, msa: &MicrosoftAuth) -> Result<Self, AuthError> {
+        let xbox_live = XboxLiveToken::fetch(ctx, msa.access_token.expose()).await?;
+        let xbox_security = XboxSecurityToken::fetch_for(ctx, xbox_live, XBOX_LIVE_RELYING_PARTY).await?;
+        Self::fetch(ctx, &xbox_security).await
+    }
+
+}
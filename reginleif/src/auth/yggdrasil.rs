@@ -0,0 +1,437 @@
+use std::collections::HashMap;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use reqwest::{Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use thiserror::Error;
+use reginleif_utils::secret::SecretString;
+use crate::auth::rate_limit::SendLimited;
+use crate::http::HttpContext;
+
+/// The header which points to the real API root, see
+/// [authlib-injector API Location Indication](https://github.com/yushijinhun/authlib-injector/wiki/%E5%90%AF%E5%8A%A8%E5%99%A8%E6%8A%80%E6%9C%AF%E8%A7%84%E8%8C%83#api-%E5%9C%B0%E5%9D%80%E6%8C%87%E7%A4%BA-ali)
+const API_LOCATION_HEADER: &str = "X-Authlib-Injector-API-Location";
+
+/// The error returned by Yggdrasil server.
+#[derive(Error, Debug)]
+pub enum YggdrasilError {
+
+    /// Wrong username or password, or the token is invalid.
+    ///
+    /// You can check error details in the inner string.
+    #[error("The operation is forbidden by Yggdrasil server. details:{0}")]
+    ForbiddenOperation(String),
+
+    /// The request is invalid, like selecting a profile which is not available.
+    ///
+    /// You can check error details in the inner string.
+    #[error("Illegal argument. details:{0}")]
+    IllegalArgument(String),
+
+    /// The account doesn't have any profile.
+    #[error("The account doesn't have any profile.")]
+    NoProfile,
+
+    /// Other error responded by the server.
+    #[error("Yggdrasil error {error}. details:{message}")]
+    Others {
+        /// The `error` field of the response.
+        error: String,
+        /// The `errorMessage` field of the response.
+        message: String,
+    },
+
+    /// Error while sending the request or parsing the response.
+    ///
+    /// You can check error details in the inner error.
+    #[error("Error while sending request. details:{0}")]
+    ReqwestError(#[from] reqwest::Error),
+
+}
+
+impl YggdrasilError {
+
+    /// Return the response if the status is success, otherwise parse the error of Yggdrasil.
+    async fn check(res: Response) -> Result<Response, YggdrasilError> {
+        if res.status().is_success() {
+            return Ok(res);
+        }
+
+        #[derive(Deserialize, Default)]
+        #[serde(rename_all = "camelCase")]
+        struct ErrorBody {
+            #[serde(default)]
+            error: String,
+            #[serde(default)]
+            error_message: String,
+        }
+
+        let status = res.status();
+        let body = res.json::<ErrorBody>().await.unwrap_or_default();
+        Err(match body.error.as_str() {
+            "ForbiddenOperationException" => YggdrasilError::ForbiddenOperation(body.error_message),
+            "IllegalArgumentException" => YggdrasilError::IllegalArgument(body.error_message),
+            "" => YggdrasilError::Others { error: status.to_string(), message: body.error_message },
+            _ => YggdrasilError::Others { error: body.error, message: body.error_message },
+        })
+    }
+
+}
+
+/// Yggdrasil Server
+///
+/// The Yggdrasil-compatible auth server, like the one of Blessing Skin or other authlib-injector servers.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct YggdrasilServer {
+    /// The API root of the server, without trailing slash, like `https://example.com/api/yggdrasil`.
+    pub root: String,
+}
+
+/// The metadata of [YggdrasilServer].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YggdrasilMetadata {
+    /// The information of the server, like `serverName` and `links`.
+    #[serde(default)]
+    pub meta: HashMap<String, Value>,
+    /// The domains the skins can be loaded from.
+    #[serde(default)]
+    pub skin_domains: Vec<String>,
+    /// The public key (PEM) which signs the properties of the profiles.
+    pub signature_publickey: Option<String>,
+}
+
+impl YggdrasilMetadata {
+
+    /// Get the name of the server.
+    pub fn server_name(&self) -> Option<&str> {
+        self.meta.get("serverName").and_then(|x| x.as_str())
+    }
+
+}
+
+/// The profile (character) of a Yggdrasil account.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct YggdrasilProfile {
+    /// uuid of the player
+    pub id: String,
+    /// name of the player
+    pub name: String,
+}
+
+/// The response of authenticate and refresh.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YggdrasilSession {
+    /// The access token you can use to launch the game.
+    pub access_token: SecretString,
+    /// The client token bound to the access token.
+    pub client_token: String,
+    /// The profiles the account can use.
+    #[serde(default)]
+    pub available_profiles: Vec<YggdrasilProfile>,
+    /// The profile bound to the access token.
+    pub selected_profile: Option<YggdrasilProfile>,
+}
+
+impl YggdrasilServer {
+
+    /// Create the server from the API root.
+    ///
+    /// # Arguments
+    /// * `root`: The API root of the server.
+    pub fn new(root: &str) -> Self {
+        Self {
+            root: root.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Create the server from the url user inputs.
+    ///
+    /// If the url responds the `X-Authlib-Injector-API-Location` header, the header will be used as the API root.
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    /// * `url`: The url user inputs, like the homepage of the skin site.
+    pub async fn resolve(ctx: &HttpContext, url: &str) -> Result<Self, YggdrasilError> {
+        let res = ctx.client.get(url).send_limited(ctx).await?;
+
+        let location = res.headers()
+            .get(API_LOCATION_HEADER)
+            .and_then(|x| x.to_str().ok())
+            .and_then(|x| res.url().join(x).ok());
+
+        Ok(match location {
+            Some(location) => Self::new(location.as_str()),
+            None => Self::new(url),
+        })
+    }
+
+    /// Fetch the metadata of the server.
+    ///
+    /// Return the metadata and the raw response, the raw response is used as `prefetched` data of authlib-injector.
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    pub async fn fetch_metadata(&self, ctx: &HttpContext) -> Result<(YggdrasilMetadata, String), YggdrasilError> {
+        let res = ctx.client.get(format!("{}/", self.root)).send_limited(ctx).await?;
+        let raw = YggdrasilError::check(res).await?.text().await?;
+        let metadata = serde_json::from_str(&raw).map_err(|e| YggdrasilError::Others {
+            error: "InvalidMetadata".to_string(),
+            message: e.to_string(),
+        })?;
+        Ok((metadata, raw))
+    }
+
+    /// Login with username (or email) and password.
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    /// * `username`: The username or email of the account.
+    /// * `password`: The password of the account.
+    /// * `client_token`: The token to identify the launcher, it should be the same for every login.
+    pub async fn authenticate(&self, ctx: &HttpContext, username: &str, password: &str, client_token: &str) -> Result<YggdrasilSession, YggdrasilError> {
+        let res = ctx.client
+            .post(format!("{}/authserver/authenticate", self.root))
+            .json(&json!({
+                "agent": {"name": "Minecraft", "version": 1},
+                "username": username,
+                "password": password,
+                "clientToken": client_token,
+                "requestUser": false
+            }))
+            .send_limited(ctx)
+            .await?;
+
+        Ok(YggdrasilError::check(res).await?.json().await?)
+    }
+
+    /// Get a new access token, the old one will be invalid.
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    /// * `access_token`: The access token.
+    /// * `client_token`: The client token bound to the access token.
+    /// * `select`: The profile to bind, only works if no profile is bound to the access token.
+    pub async fn refresh(&self, ctx: &HttpContext, access_token: &str, client_token: &str, select: Option<&YggdrasilProfile>) -> Result<YggdrasilSession, YggdrasilError> {
+        let mut body = json!({
+            "accessToken": access_token,
+            "clientToken": client_token,
+            "requestUser": false
+        });
+        if let Some(profile) = select {
+            body["selectedProfile"] = json!(profile);
+        }
+
+        let res = ctx.client
+            .post(format!("{}/authserver/refresh", self.root))
+            .json(&body)
+            .send_limited(ctx)
+            .await?;
+
+        Ok(YggdrasilError::check(res).await?.json().await?)
+    }
+
+    /// Check the access token is valid or not.
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    /// * `access_token`: The access token.
+    /// * `client_token`: The client token bound to the access token.
+    pub async fn validate(&self, ctx: &HttpContext, access_token: &str, client_token: &str) -> Result<bool, YggdrasilError> {
+        let res = ctx.client
+            .post(format!("{}/authserver/validate", self.root))
+            .json(&json!({
+                "accessToken": access_token,
+                "clientToken": client_token
+            }))
+            .send_limited(ctx)
+            .await?;
+
+        match YggdrasilError::check(res).await {
+            Ok(_) => Ok(true),
+            Err(YggdrasilError::ForbiddenOperation(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Make the access token invalid, this is what logout does.
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    /// * `access_token`: The access token.
+    /// * `client_token`: The client token bound to the access token.
+    pub async fn invalidate(&self, ctx: &HttpContext, access_token: &str, client_token: &str) -> Result<(), YggdrasilError> {
+        let res = ctx.client
+            .post(format!("{}/authserver/invalidate", self.root))
+            .json(&json!({
+                "accessToken": access_token,
+                "clientToken": client_token
+            }))
+            .send_limited(ctx)
+            .await?;
+
+        // the token may be invalid already, which is what we want.
+        if res.status() == StatusCode::FORBIDDEN {
+            return Ok(());
+        }
+        YggdrasilError::check(res).await?;
+        Ok(())
+    }
+
+}
+
+/// Yggdrasil Account
+///
+/// The account login with a Yggdrasil server, the game should be launched with authlib-injector,
+/// see [jvm_args](YggdrasilAccount::jvm_args).
+///
+/// # Example
+/// ```no_run
+/// use reginleif::http::HttpContext;
+/// use reginleif::auth::yggdrasil::{YggdrasilAccount, YggdrasilLogin, YggdrasilServer};
+///
+/// async fn example(){
+///     let ctx = HttpContext::default();
+///     let server = YggdrasilServer::resolve(&ctx,"https://example.com").await.unwrap();
+///
+///     let account = match YggdrasilAccount::login(&ctx,&server,"user@example.com","password").await.unwrap() {
+///         YggdrasilLogin::Ready(account) => account,
+///         YggdrasilLogin::SelectProfile(select) => {
+///             let profile = select.session.available_profiles[0].clone(); // ask the user.
+///             select.select(&ctx,&profile).await.unwrap()
+///         }
+///     };
+///
+///     println!("{:?}",account.jvm_args("authlib-injector.jar"));
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct YggdrasilAccount {
+    pub server: YggdrasilServer,
+    pub access_token: SecretString,
+    pub client_token: String,
+    pub profile: YggdrasilProfile,
+    /// The raw metadata of the server encoded in base64, it's passed to authlib-injector to skip fetching.
+    pub prefetched: Option<String>,
+}
+
+/// The result of [YggdrasilAccount::login].
+#[derive(Debug, Clone)]
+pub enum YggdrasilLogin {
+    /// The account is ready to use.
+    Ready(YggdrasilAccount),
+    /// The account has more than one profile, use [YggdrasilProfileSelect::select] to choose one.
+    SelectProfile(YggdrasilProfileSelect),
+}
+
+/// The account which is signed in but no profile is selected.
+#[derive(Debug, Clone)]
+pub struct YggdrasilProfileSelect {
+    pub server: YggdrasilServer,
+    pub session: YggdrasilSession,
+    pub prefetched: Option<String>,
+}
+
+impl YggdrasilProfileSelect {
+
+    /// Bind the profile to the access token and build the account.
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    /// * `profile`: One of [available_profiles](YggdrasilSession::available_profiles).
+    pub async fn select(&self, ctx: &HttpContext, profile: &YggdrasilProfile) -> Result<YggdrasilAccount, YggdrasilError> {
+        let session = self.server
+            .refresh(ctx, self.session.access_token.expose(), &self.session.client_token, Some(profile))
+            .await?;
+
+        YggdrasilAccount::from_session(self.server.clone(), session, self.prefetched.clone())
+            .ok_or(YggdrasilError::NoProfile)
+    }
+
+}
+
+impl YggdrasilAccount {
+
+    /// Login with username (or email) and password, and fetch the metadata of the server.
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    /// * `server`: The Yggdrasil server.
+    /// * `username`: The username or email of the account.
+    /// * `password`: The password of the account.
+    pub async fn login(ctx: &HttpContext, server: &YggdrasilServer, username: &str, password: &str) -> Result<YggdrasilLogin, YggdrasilError> {
+        let (_, raw) = server.fetch_metadata(ctx).await?;
+        let prefetched = Some(STANDARD.encode(raw));
+
+        let client_token = uuid::Uuid::new_v4().simple().to_string();
+        let session = server.authenticate(ctx, username, password, &client_token).await?;
+
+        if session.selected_profile.is_some() {
+            let account = Self::from_session(server.clone(), session, prefetched).ok_or(YggdrasilError::NoProfile)?;
+            return Ok(YggdrasilLogin::Ready(account));
+        }
+        if session.available_profiles.is_empty() {
+            return Err(YggdrasilError::NoProfile);
+        }
+
+        Ok(YggdrasilLogin::SelectProfile(YggdrasilProfileSelect {
+            server: server.clone(),
+            session,
+            prefetched,
+        }))
+    }
+
+    fn from_session(server: YggdrasilServer, session: YggdrasilSession, prefetched: Option<String>) -> Option<Self> {
+        Some(Self {
+            server,
+            access_token: session.access_token,
+            client_token: session.client_token,
+            profile: session.selected_profile?,
+            prefetched,
+        })
+    }
+
+    /// Check the access token is valid or not.
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    pub async fn validate(&self, ctx: &HttpContext) -> Result<bool, YggdrasilError> {
+        self.server.validate(ctx, self.access_token.expose(), &self.client_token).await
+    }
+
+    /// Get a new access token and update the account.
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    pub async fn renew(&mut self, ctx: &HttpContext) -> Result<(), YggdrasilError> {
+        let session = self.server.refresh(ctx, self.access_token.expose(), &self.client_token, None).await?;
+        self.access_token = session.access_token;
+        if let Some(profile) = session.selected_profile {
+            self.profile = profile;
+        }
+        Ok(())
+    }
+
+    /// Logout, the access token will be invalid.
+    ///
+    /// # Arguments
+    /// * `ctx`: The HTTP context.
+    pub async fn invalidate(&self, ctx: &HttpContext) -> Result<(), YggdrasilError> {
+        self.server.invalidate(ctx, self.access_token.expose(), &self.client_token).await
+    }
+
+    /// Get the JVM arguments to load authlib-injector, put them before the main class.
+    ///
+    /// # Arguments
+    /// * `agent_path`: The path of authlib-injector jar.
+    pub fn jvm_args(&self, agent_path: &str) -> Vec<String> {
+        let mut args = vec![format!("-javaagent:{}={}", agent_path, self.server.root)];
+        if let Some(prefetched) = &self.prefetched {
+            args.push(format!("-Dauthlibinjector.yggdrasil.prefetched={}", prefetched));
+        }
+        args
+    }
+
+}
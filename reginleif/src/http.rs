@@ -0,0 +1,178 @@
+use std::sync::Arc;
+use std::time::Duration;
+use reqwest::{Certificate, Client, ClientBuilder, Proxy};
+use crate::auth::endpoint::AuthEndpoints;
+use crate::auth::rate_limit::{RateLimitConfig, RateLimiter};
+
+/// The user agent sent by [HttpContext::default].
+pub const DEFAULT_USER_AGENT: &str = concat!("reginleif/", env!("CARGO_PKG_VERSION"));
+
+/// HTTP Context
+///
+/// This struct holds the reqwest client, the endpoints and the rate limiter which every request of
+/// [auth](crate::auth) and [metadata](crate::metadata) is sent with, so the proxy, the user agent,
+/// the timeouts and the root certificates you configured are used everywhere, including
+/// the refresh of [Account](crate::auth::account::Account).
+///
+/// The client shares the connection pool and the limiter shares the rate limit when cloned,
+/// so clone the context instead of creating a new one.
+///
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+/// use reqwest::Proxy;
+/// use reginleif::auth::endpoint::AuthEndpoints;
+/// use reginleif::http::HttpContext;
+///
+/// let default = HttpContext::default();
+///
+/// let ctx = HttpContext::builder()
+///     .user_agent("MyLauncher/1.0")
+///     .proxy(Proxy::all("http://127.0.0.1:7890").unwrap())
+///     .timeout(Duration::from_secs(30))
+///     .endpoints(AuthEndpoints::default().with_microsoft_authority("https://login.microsoftonline.com/organizations"))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct HttpContext {
+    /// The client to send the requests.
+    pub client: Client,
+    /// The endpoints that the auth chain will request.
+    pub endpoints: AuthEndpoints,
+    /// The limiter that the requests of [auth](crate::auth) are sent through.
+    pub limiter: Arc<RateLimiter>,
+}
+
+impl Default for HttpContext {
+    /// The client with [DEFAULT_USER_AGENT] and the production endpoints.
+    fn default() -> Self {
+        Self::builder().build().expect("the default client should be built")
+    }
+}
+
+impl HttpContext {
+
+    /// Create the context with your own client, the production endpoints and the default rate limit.
+    ///
+    /// # Arguments
+    /// * `client`: The reqwest client.
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            endpoints: AuthEndpoints::default(),
+            limiter: Default::default(),
+        }
+    }
+
+    /// Replace the endpoints, like pointing the auth chain to a mock server.
+    ///
+    /// # Arguments
+    /// * `endpoints`: The endpoints of the auth chain.
+    pub fn with_endpoints(mut self, endpoints: AuthEndpoints) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
+    /// Build the context with the options of the client.
+    pub fn builder() -> HttpContextBuilder {
+        HttpContextBuilder::default()
+    }
+
+}
+
+/// The builder of [HttpContext].
+#[derive(Debug)]
+pub struct HttpContextBuilder {
+    client: ClientBuilder,
+    user_agent: String,
+    endpoints: AuthEndpoints,
+    rate_limit: RateLimitConfig,
+}
+
+impl Default for HttpContextBuilder {
+    fn default() -> Self {
+        Self {
+            client: Client::builder(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            endpoints: AuthEndpoints::default(),
+            rate_limit: RateLimitConfig::default(),
+        }
+    }
+}
+
+impl HttpContextBuilder {
+
+    /// Set the `User-Agent` header, [DEFAULT_USER_AGENT] by default.
+    ///
+    /// # Arguments
+    /// * `user_agent`: The user agent, like `MyLauncher/1.0`.
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    /// Add a proxy, it can be called multiple times.
+    ///
+    /// # Arguments
+    /// * `proxy`: The proxy to send the requests through.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.client = self.client.proxy(proxy);
+        self
+    }
+
+    /// Set the timeout of the whole request, there's no timeout by default.
+    ///
+    /// # Arguments
+    /// * `timeout`: The timeout of the request.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.client = self.client.timeout(timeout);
+        self
+    }
+
+    /// Set the timeout of the connect phase.
+    ///
+    /// # Arguments
+    /// * `timeout`: The timeout of connecting.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.client = self.client.connect_timeout(timeout);
+        self
+    }
+
+    /// Trust an additional root certificate, like the one of a corporate proxy.
+    ///
+    /// # Arguments
+    /// * `cert`: The certificate to trust.
+    pub fn add_root_certificate(mut self, cert: Certificate) -> Self {
+        self.client = self.client.add_root_certificate(cert);
+        self
+    }
+
+    /// Set the endpoints, the production endpoints by default.
+    ///
+    /// # Arguments
+    /// * `endpoints`: The endpoints of the auth chain.
+    pub fn endpoints(mut self, endpoints: AuthEndpoints) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
+    /// Set the rate limit of the requests, [RateLimitConfig::default] by default.
+    ///
+    /// # Arguments
+    /// * `config`: The config of the limiter.
+    pub fn rate_limit(mut self, config: RateLimitConfig) -> Self {
+        self.rate_limit = config;
+        self
+    }
+
+    /// Build the context, fails if the client can't be built (like the TLS backend can't be initialized).
+    pub fn build(self) -> reqwest::Result<HttpContext> {
+        Ok(HttpContext {
+            client: self.client.user_agent(self.user_agent).build()?,
+            endpoints: self.endpoints,
+            limiter: Arc::new(RateLimiter::new(self.rate_limit)),
+        })
+    }
+
+}
@@ -4,3 +4,5 @@
 pub mod auth;
 /// All the thing that related to utils that is need across the package.
 pub mod metadata;
+/// The shared HTTP context (client and endpoints) for every request of the package.
+pub mod http;
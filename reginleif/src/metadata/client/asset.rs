@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 use std::marker::PhantomData;
-use reqwest::Client;
+use crate::http::HttpContext;
 use serde::Deserialize;
 use reginleif_macro::{Cache};
 use reginleif_utils::save_path::{BaseStorePoint, Cache};
@@ -27,14 +27,14 @@ pub struct AssetInfo<T> where T:BaseStorePoint{
 
 impl <T> AssetInfo<T> where T:BaseStorePoint+Clone{
 
-     pub async fn fetch(base_on:&T,client: Client,url:&str,id:&str) -> anyhow::Result<Self>{
+     pub async fn fetch(base_on:&T,ctx: &HttpContext,url:&str,id:&str) -> anyhow::Result<Self>{
          Self::builder()
              .base_on(base_on)
              .url(url)
              .add("assets")
              .add("indexes")
              .add(format!("{}.json",id))
-             .build_try(client).await // skip check
+             .build_try(ctx.client.clone()).await // skip check
      }
 
 }
@@ -48,7 +48,7 @@ pub struct AssetIndex{
 }
 
 impl AssetIndex {
-    pub async fn fetch_assets_info<T:BaseStorePoint+Clone>(&self,base_on:&T,client: Client) -> anyhow::Result<AssetInfo<T>>{
-        AssetInfo::fetch(base_on,client,&self.url,&self.id).await
+    pub async fn fetch_assets_info<T:BaseStorePoint+Clone>(&self,base_on:&T,ctx: &HttpContext) -> anyhow::Result<AssetInfo<T>>{
+        AssetInfo::fetch(base_on,ctx,&self.url,&self.id).await
     }
 }
\ No newline at end of file
@@ -88,7 +88,7 @@ impl Platform{
     }
 
     pub fn allow_rule(&self,rules:Vec<Rule>)->bool{
-        if rules.len() == 0 {
+        if rules.is_empty() {
             return true;
         }
         
@@ -1,6 +1,6 @@
 use std::marker::PhantomData;
 use std::slice::Iter;
-use reqwest::Client;
+use crate::http::HttpContext;
 use serde::{Deserialize, Serialize};
 use reginleif_macro::{Cache, Storage};
 use reginleif_utils::save_path::{BaseStorePoint, Cache, Store};
@@ -22,8 +22,8 @@ pub struct PackageInfo{
 }
 
 impl PackageInfo{
-    pub async fn get_details<T:BaseStorePoint+Clone>(&self,base_on:&T,client: Client,url:&str) -> anyhow::Result<PackageDetails<T>>{
-        PackageDetails::fetch(base_on,client,url,self).await
+    pub async fn get_details<T:BaseStorePoint+Clone>(&self,base_on:&T,ctx: &HttpContext,url:&str) -> anyhow::Result<PackageDetails<T>>{
+        PackageDetails::fetch(base_on,ctx,url,self).await
     }
 }
 
@@ -50,7 +50,7 @@ impl <T> PackageList<T> where T:BaseStorePoint{
 }
 
 impl <T> PackageList<T> where T:BaseStorePoint+Clone{
-    pub async fn fetch(base_on:&T, client: Client, url: &str) -> anyhow::Result<Self>{
+    pub async fn fetch(base_on:&T, ctx: &HttpContext, url: &str) -> anyhow::Result<Self>{
         let mut builder = Self::builder()
             .base_on(base_on)
             .url(url);
@@ -59,10 +59,10 @@ impl <T> PackageList<T> where T:BaseStorePoint+Clone{
             builder = builder.add(i);
         }
 
-        builder.build_try(client).await
+        builder.build_try(ctx.client.clone()).await
     }
     
-    pub async fn refresh(base_on:&T, client: Client, url: &str) -> anyhow::Result<Self>{
+    pub async fn refresh(base_on:&T, ctx: &HttpContext, url: &str) -> anyhow::Result<Self>{
         let mut builder = Self::builder()
             .base_on(base_on)
             .url(url);
@@ -71,7 +71,7 @@ impl <T> PackageList<T> where T:BaseStorePoint+Clone{
             builder = builder.add(i);
         }
 
-        builder.build_refresh(client).await
+        builder.build_refresh(ctx.client.clone()).await
     }
 }
 
@@ -117,12 +117,12 @@ impl <T> PackageDetails<T> where T:BaseStorePoint{
 
 impl <T> PackageDetails<T> where T:BaseStorePoint+Clone{
     /// fetch the package details from the server.
-    pub async fn fetch(base_on:&T, client: Client, base_url:&str, package_info: &PackageInfo) -> anyhow::Result<Self>{
+    pub async fn fetch(base_on:&T, ctx: &HttpContext, base_url:&str, package_info: &PackageInfo) -> anyhow::Result<Self>{
         Self::builder()
             .base_on(base_on)
             .url(format!("{}/{}/index.json",base_url,package_info.uid))
             .add(&package_info.uid)
             .add("index.json")
-            .build_check(client.clone(),package_info.sha256.clone()).await
+            .build_check(ctx.client.clone(),package_info.sha256.clone()).await
     }
 }
\ No newline at end of file
@@ -1,5 +1,5 @@
 use std::marker::PhantomData;
-use reqwest::Client;
+use crate::http::HttpContext;
 use serde::{Deserialize, Serialize};
 use reginleif_macro::Cache;
 use reginleif_utils::save_path::{BaseStorePoint, Cache};
@@ -35,8 +35,8 @@ pub struct VersionInfo{
 }
 
 impl VersionInfo {
-    pub async fn get_details<T:BaseStorePoint+Clone>(&self,base_on:&T,client: Client, base_url:&str, uid:&str) -> anyhow::Result<VersionDetails<T>>{
-        VersionDetails::fetch(base_on,client,base_url,uid,self).await
+    pub async fn get_details<T:BaseStorePoint+Clone>(&self,base_on:&T,ctx: &HttpContext, base_url:&str, uid:&str) -> anyhow::Result<VersionDetails<T>>{
+        VersionDetails::fetch(base_on,ctx,base_url,uid,self).await
     }
 }
 
@@ -72,12 +72,12 @@ pub struct VersionDetails<T> where T:BaseStorePoint{
 
 impl <T> VersionDetails<T> where T:BaseStorePoint+Clone{
     /// Fetch the version details from the server.
-    pub async fn fetch(base_on:&T, client: Client, base_url:&str, uid:&str, version_info: &VersionInfo) -> anyhow::Result<Self>{
+    pub async fn fetch(base_on:&T, ctx: &HttpContext, base_url:&str, uid:&str, version_info: &VersionInfo) -> anyhow::Result<Self>{
         Self::builder()
             .base_on(base_on)
             .url(format!("{}/{}/{}.json",base_url,uid,version_info.version))
             .add(uid)
             .add(format!("{}.json",version_info.version))
-            .build_check(client,version_info.sha256.clone()).await
+            .build_check(ctx.client.clone(),version_info.sha256.clone()).await
     }
 }   
\ No newline at end of file